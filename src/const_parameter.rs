@@ -1,2 +1,9 @@
 pub const DISPLAY_WIDTH: usize = 480;
 pub const DISPLAY_HEIGHT: usize = 480;
+pub const CORE_SAMPLE_RATE: f32 = 30.0;
+pub const DEFAULT_TRACK_LENGTH: usize = 480;
+pub const DEFAULT_DATA_DIRECTORY: &str = "data";
+pub const DEFAULT_SETTINGS_FILE: &str = "settings.conf";
+pub const DEFAULT_TEMPO: f32 = 120.0;
+pub const MIN_TEMPO: f32 = 20.0;
+pub const MAX_TEMPO: f32 = 300.0;
@@ -2,6 +2,7 @@ mod view;
 mod model;
 mod controller;
 mod const_parameter;
+mod settings;
 
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -18,17 +19,92 @@ use std::env;
 use std::path::PathBuf;
 use crate::model::track_loader::WaveGenerateType::Noise;
 use crate::view::view_main::{ViewContainer};
-use log::{info};
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_map::KeyMap;
+use crate::view::interaction::touch_manager::TouchManager;
+use crate::view::interaction::script_input::ScriptInput;
+use crate::const_parameter::{DISPLAY_WIDTH, DISPLAY_HEIGHT, DEFAULT_SETTINGS_FILE};
+use crate::settings::{SettingValue, Settings};
+use log::{info, warn};
 
 fn main() {
     env_logger::init();
     info!("starting up info");
-    
-    let mut view_container = ViewContainer::new(30.0, 4);
+
+    // --record <file>: write every input event with its frame number
+    // --replay <file>: feed a recorded file instead of the keyboard, exit when done
+    // --keymap <file>: physical key to logical action bindings
+    // --headless: no keyboard device, drive the UI with --script-stdin or --script-socket
+    // --script-stdin: read logical key commands from stdin, exit at EOF
+    // --script-socket <path>: read logical key commands from a Unix socket
+    // --touch <device>: evdev touch panel, e.g. /dev/input/event0
+    // --touch-range <x> <y>: raw maximum of the touch panel axes, defaults to the display size
+    // --config <path>: settings file, defaults to ./settings.conf
+    // --data-dir <path>: directory listed by the file browser, overrides the data directory setting
+    // --status-bar: show transport, playback position and frame time above the pages
+    // --frame-stats: draw render/flush time and frame time percentiles over the pages
+    // --adaptive-fps: lower the frame rate under load and while the screen does not change
+    let args: Vec<String> = env::args().collect();
+    let arg_value = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1)).cloned();
+
+    let mut input_manager = match arg_value("--replay") {
+        Some(path) => InputManager::from_replay(&path).expect("Unable to open input replay file"),
+        None if args.iter().any(|a| a == "--headless") => InputManager::new_headless(),
+        None => InputManager::new(),
+    };
+    if args.iter().any(|a| a == "--script-stdin") {
+        input_manager.set_script_input(ScriptInput::from_stdin());
+    }
+    #[cfg(unix)]
+    if let Some(path) = arg_value("--script-socket") {
+        input_manager.set_script_input(ScriptInput::from_unix_socket(&path).expect("Unable to open script socket"));
+    }
+    if let Some(path) = arg_value("--keymap") {
+        input_manager.set_key_map(KeyMap::from_file(&path).expect("Unable to load keymap file"));
+    }
+    if let Some(path) = arg_value("--touch") {
+        let touch_range = match args.iter().position(|a| a == "--touch-range") {
+            Some(i) => {
+                let range_value = |offset: usize| args.get(i + offset).and_then(|v| v.parse().ok()).expect("Invalid touch range");
+                [range_value(1), range_value(2)]
+            },
+            None => [DISPLAY_WIDTH as i32, DISPLAY_HEIGHT as i32],
+        };
+        match TouchManager::new(&path, touch_range, [DISPLAY_WIDTH, DISPLAY_HEIGHT]) {
+            Ok(touch_manager) => input_manager.set_touch_manager(touch_manager),
+            Err(e) => warn!("Touch input disabled, unable to open {}: {}", path, e),
+        }
+    }
+    if let Some(path) = arg_value("--record") {
+        input_manager.record_to_file(&path).expect("Unable to create input record file");
+    }
+
+    let settings_path = PathBuf::from(arg_value("--config").unwrap_or_else(|| String::from(DEFAULT_SETTINGS_FILE)));
+    let mut settings = Settings::load(&settings_path);
+    if let Some(data_directory) = arg_value("--data-dir") {
+        if let Err(e) = settings.set_override("data_directory", SettingValue::Text(data_directory)) {
+            warn!("{}", e);
+        }
+    }
+    let mut view_container = ViewContainer::new(30.0, 4, input_manager, settings);
+    if args.iter().any(|a| a == "--status-bar") {
+        view_container.enable_status_bar();
+    }
+    if args.iter().any(|a| a == "--frame-stats") {
+        view_container.enable_frame_stats_overlay();
+    }
+    if args.iter().any(|a| a == "--adaptive-fps") {
+        view_container.enable_adaptive_frame_rate();
+    }
     view_container.frame_init();
     loop{
         view_container.frame_start();
         view_container.frame_main();
         view_container.frame_end();
+        if view_container.is_input_finished() {
+            info!("Input finished");
+            info!("Frame timing: {}", view_container.frame_stats().summary());
+            break;
+        }
     }
 }
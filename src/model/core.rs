@@ -3,9 +3,49 @@ use crate::model::data_loader_container::*;
 use crate::model::wave_container::*;
 use crate::model::operator_rack::*;
 use crate::model::data_output::*;
+use crate::const_parameter::{CORE_SAMPLE_RATE, DEFAULT_TRACK_LENGTH, DEFAULT_TEMPO, MIN_TEMPO, MAX_TEMPO};
+
+/// Edits requested by the view layer, applied with `Core::execute`
+#[derive(Clone, Debug)]
+pub enum CoreCommand {
+    SetTrackLoader { track_index: usize, loader_type: String, wave_generate_type: WaveGenerateType },
+    ReadTrackFromFile { track_index: usize, file_path: String },
+    SaveTrackToFile { track_index: usize, file_path: String },
+    SetGeneratorParameter { track_index: usize, parameter: GeneratorParameter },
+    RenameTrack { track_index: usize, name: String },
+    SelectTrack(usize),
+    SetPointer(usize),
+    MarkIn,
+    MarkOut,
+    CancelInOut,
+    Copy,
+    Paste,
+    Insert,
+    AddOperator { operator_type: String },
+    RemoveOperator(usize),
+    ConnectPorts { src_node_id: usize, src_port_id: usize, dst_node_id: usize, dst_port_id: usize },
+    DisconnectPort { node_id: usize, port_id: usize, is_input: bool },
+    TogglePlay,
+    SetTempo(f32),
+    SetRecording { track_index: usize, recording: bool },
+    SetMuted { track_index: usize, muted: bool },
+    SetTrackLength(usize),
+    SetSampleRate(f32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransportState {
+    Stopped,
+    Playing,
+}
 
 pub struct Core {
     track_number: usize,
+    track_length: usize,
+    time: usize,
+    transport: TransportState,
+    tempo: f32,
+    sample_rate: f32,
     pub data_loader_container: DataLoaderContainer,
     pub wave_container: WaveContainer,
     pub operator_rack: OperatorRack,
@@ -16,6 +56,11 @@ impl Core {
     pub fn new (track_number:usize) -> Self {
         Core {
             track_number,
+            track_length: DEFAULT_TRACK_LENGTH,
+            time: 0,
+            transport: TransportState::Playing,
+            tempo: DEFAULT_TEMPO,
+            sample_rate: CORE_SAMPLE_RATE,
             data_loader_container: DataLoaderContainer::new(track_number),
             wave_container: WaveContainer::new(track_number),
             operator_rack: OperatorRack::new(track_number),
@@ -25,31 +70,157 @@ impl Core {
 
     pub fn core_loop (&mut self) {
         // loop track_loader from DataLoaderContainer
-        self.data_loader_container.loop_update_track_loader_container(5);
+        self.data_loader_container.loop_update_track_loader_container(self.time);
         // loop wave_track from WaveContainer
         // loop OperatorRack
+        let input_values: Vec<i32> = self.wave_container.wave_track.iter().enumerate().map(|(track_index, track)| {
+            let track = track.borrow();
+            if track.is_empty() || self.wave_container.is_muted(track_index) { 0 } else { track[self.time % track.len()] }
+        }).collect();
+        self.operator_rack.set_input_values(&input_values);
+        self.operator_rack.compute();
         // loop DataOutput
+        if self.transport == TransportState::Playing {
+            self.time = (self.time + 1) % self.track_length;
+        }
+    }
+
+    pub fn execute (&mut self, command: CoreCommand) -> Result<(), String> {
+        match command {
+            CoreCommand::SetTrackLoader { track_index, loader_type, wave_generate_type } => {
+                self.check_track_index(track_index)?;
+                self.set_track_loader(track_index, &loader_type, wave_generate_type);
+            },
+            CoreCommand::ReadTrackFromFile { track_index, file_path } => {
+                self.check_track_index(track_index)?;
+                self.read_track_from_file(track_index, &file_path)?;
+            },
+            CoreCommand::SaveTrackToFile { track_index, file_path } => {
+                self.check_track_index(track_index)?;
+                self.save_track_to_file(track_index, &file_path)?;
+            },
+            CoreCommand::SetGeneratorParameter { track_index, parameter } => {
+                self.check_track_index(track_index)?;
+                match &mut self.data_loader_container.track_loader[track_index] {
+                    TrackLoader::WaveGenerator(generator) => generator.set_parameter(parameter),
+                    _ => return Err(format!("Track {} has no wave generator", track_index)),
+                }
+            },
+            CoreCommand::RenameTrack { track_index, name } => {
+                self.check_track_index(track_index)?;
+                self.wave_container.set_track_name(track_index, &name);
+            },
+            CoreCommand::SelectTrack(track_index) => {
+                self.check_track_index(track_index)?;
+                self.wave_container.select_track(track_index);
+            },
+            CoreCommand::SetPointer(pointer) => self.wave_container.set_pointer(pointer),
+            CoreCommand::MarkIn => self.wave_container.mark_in_flag(),
+            CoreCommand::MarkOut => self.wave_container.mark_out_flag(),
+            CoreCommand::CancelInOut => self.wave_container.cancel_in_out_flag(),
+            CoreCommand::Copy => self.wave_container.copy_in_out_track(),
+            CoreCommand::Paste => self.wave_container.paste_in_out_track(),
+            CoreCommand::Insert => self.wave_container.insert_in_out_track(),
+            CoreCommand::AddOperator { operator_type } => {
+                let operator = create_operator(&operator_type).ok_or_else(|| format!("Unknown operator type {}", operator_type))?;
+                let node_id = self.operator_rack.next_node_id();
+                self.operator_rack.add_node(node_id, operator);
+            },
+            CoreCommand::RemoveOperator(node_id) => self.operator_rack.remove_node(node_id)?,
+            CoreCommand::ConnectPorts { src_node_id, src_port_id, dst_node_id, dst_port_id } => {
+                self.operator_rack.try_connect(src_node_id, src_port_id, dst_node_id, dst_port_id)?;
+            },
+            CoreCommand::DisconnectPort { node_id, port_id, is_input } => self.operator_rack.disconnect_port(node_id, port_id, is_input),
+            CoreCommand::TogglePlay => {
+                self.transport = match self.transport {
+                    TransportState::Playing => TransportState::Stopped,
+                    TransportState::Stopped => TransportState::Playing,
+                };
+            },
+            CoreCommand::SetTempo(tempo) => {
+                if !(MIN_TEMPO..=MAX_TEMPO).contains(&tempo) {
+                    return Err(format!("Tempo {} is out of range {}-{}", tempo, MIN_TEMPO, MAX_TEMPO));
+                }
+                self.tempo = tempo;
+            },
+            CoreCommand::SetRecording { track_index, recording } => {
+                self.check_track_index(track_index)?;
+                self.wave_container.set_recording(track_index, recording);
+            },
+            CoreCommand::SetMuted { track_index, muted } => {
+                self.check_track_index(track_index)?;
+                self.wave_container.set_muted(track_index, muted);
+            },
+            CoreCommand::SetTrackLength(track_length) => {
+                if track_length == 0 {
+                    return Err(String::from("Track length must not be 0"));
+                }
+                self.track_length = track_length;
+                self.time %= track_length;
+            },
+            CoreCommand::SetSampleRate(sample_rate) => {
+                if sample_rate <= 0.0 {
+                    return Err(format!("Invalid sample rate {}", sample_rate));
+                }
+                self.sample_rate = sample_rate;
+            },
+        }
+        Ok(())
+    }
+
+    fn check_track_index (&self, track_index: usize) -> Result<(), String> {
+        if track_index < self.track_number {
+            Ok(())
+        } else {
+            Err(format!("Track {} does not exist", track_index))
+        }
+    }
+
+    /// Playback position, advanced by one sample per `core_loop`
+    pub fn time (&self) -> usize {
+        self.time
+    }
+
+    pub fn track_length (&self) -> usize {
+        self.track_length
+    }
+
+    pub fn transport (&self) -> TransportState {
+        self.transport
+    }
+
+    /// `core_loop` calls per second
+    pub fn sample_rate (&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Beats per minute
+    pub fn tempo (&self) -> f32 {
+        self.tempo
+    }
+
+    pub fn track_number (&self) -> usize {
+        self.track_number
     }
-    
 
     pub fn set_track_loader (&mut self, loader_index:usize, loader_type:&str, wave_generate_type: WaveGenerateType) {
         self.data_loader_container.set_track_loader(loader_index, loader_type, wave_generate_type, &self.wave_container);
     }
 
-    fn read_track_from_file(&mut self, loader_index:usize, file_path:&str) {
+    fn read_track_from_file(&mut self, loader_index:usize, file_path:&str) -> Result<(), String> {
         if let TrackLoader::FileLoader(ref mut loader) = &mut self.data_loader_container.track_loader[loader_index] {
-            loader.read_track_from_file(file_path).unwrap();
+            loader.read_track_from_file(file_path).map_err(|e| format!("{}: {}", file_path, e))
         } else {
-            todo!();
+            Err(format!("Track {} has no file loader", loader_index))
         }
     }
 
-    fn save_track_from_file () {
-        todo!();
+    fn save_track_to_file(&self, track_index:usize, file_path:&str) -> Result<(), String> {
+        self.wave_container.save_track_to_file(track_index, file_path).map_err(|e| format!("{}: {}", file_path, e))
     }
 
     fn push_data_to_track () {
         todo!();
     }
 
-}
\ No newline at end of file
+}
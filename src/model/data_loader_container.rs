@@ -36,14 +36,12 @@ impl DataLoaderContainer {
     }
     
     pub fn loop_update_track_loader_container(&mut self, time:usize) {
-        for i in &self.track_loader {
+        for i in &mut self.track_loader {
            match i {
-               TrackLoader::FileLoader(i) => todo!(),
-               TrackLoader::WaveGenerator(i) => {
-                   println!("{}", i.get_wave_value(time));
-                   println!("{:?}", i.wave_date_buffer);
-               }
-               _=> println!("other! loop_update_track_loader_container")
+               // file data is loaded once, nothing to stream
+               TrackLoader::FileLoader(_) => {},
+               TrackLoader::WaveGenerator(i) => i.push_value_to_track(time),
+               _=> {}
            }
         }
     }
@@ -3,4 +3,5 @@ pub mod track_loader;
 pub mod wave_container;
 pub mod operator_rack;
 pub mod data_output;
-pub mod core;
\ No newline at end of file
+pub mod core;
+pub mod spectrum;
\ No newline at end of file
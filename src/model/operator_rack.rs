@@ -10,6 +10,25 @@ pub trait Operator {
     fn compute (&mut self);
     fn get_input_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>>;
     fn get_output_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>>;
+    fn get_name(&self) -> &str;
+    fn input_port_number(&self) -> usize;
+    fn output_port_number(&self) -> usize;
+}
+
+/// Operator types that can be added to the rack, see `create_operator`
+pub const OPERATOR_TYPES: [&str; 3] = ["add", "minus", "multiply"];
+
+pub fn create_operator(operator_type: &str) -> Option<Box<dyn Operator>> {
+    match operator_type {
+        "add" => Some(Box::new(OperatorAdd::new())),
+        "minus" => Some(Box::new(OperatorMinus::new())),
+        "multiply" => Some(Box::new(OperatorMultiply::new())),
+        _ => None,
+    }
+}
+
+fn new_ports(number: usize) -> HashMap<usize, Rc<RefCell<Port>>> {
+    (0..number).map(|i| (i, Rc::new(RefCell::new(Port { value: 0 })))).collect()
 }
 
 pub struct OperatorAdd {
@@ -55,12 +74,102 @@ impl Operator for OperatorAdd {
     fn get_output_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>> {
         self.output_ports.get(port_id)
     }
+
+    fn get_name(&self) -> &str {
+        "Add"
+    }
+
+    fn input_port_number(&self) -> usize {
+        self.input_ports.len()
+    }
+
+    fn output_port_number(&self) -> usize {
+        self.output_ports.len()
+    }
+}
+
+/// Input 0 minus input 1
+pub struct OperatorMinus {
+    input_ports: HashMap<usize, Rc<RefCell<Port>>>,
+    output_ports: HashMap<usize, Rc<RefCell<Port>>>,
+}
+
+impl OperatorMinus {
+    pub fn new() -> Self {
+        OperatorMinus {
+            input_ports: new_ports(2),
+            output_ports: new_ports(1),
+        }
+    }
+}
+
+impl Operator for OperatorMinus {
+    fn compute(&mut self) {
+        let result = self.input_ports[&0].borrow().value.saturating_sub(self.input_ports[&1].borrow().value);
+        self.output_ports[&0].borrow_mut().value = result;
+    }
+
+    fn get_input_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>> {
+        self.input_ports.get(port_id)
+    }
+
+    fn get_output_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>> {
+        self.output_ports.get(port_id)
+    }
+
+    fn get_name(&self) -> &str {
+        "Minus"
+    }
+
+    fn input_port_number(&self) -> usize {
+        self.input_ports.len()
+    }
+
+    fn output_port_number(&self) -> usize {
+        self.output_ports.len()
+    }
+}
+
+pub struct OperatorMultiply {
+    input_ports: HashMap<usize, Rc<RefCell<Port>>>,
+    output_ports: HashMap<usize, Rc<RefCell<Port>>>,
+}
+
+impl OperatorMultiply {
+    pub fn new() -> Self {
+        OperatorMultiply {
+            input_ports: new_ports(2),
+            output_ports: new_ports(1),
+        }
+    }
 }
 
-// struct OperatorMinus {
-//     input_port: Vec<Rc<RefCell<i32>>>,
-//     output_port: Rc<RefCell<i32>>,
-// }
+impl Operator for OperatorMultiply {
+    fn compute(&mut self) {
+        let result = self.input_ports[&0].borrow().value.saturating_mul(self.input_ports[&1].borrow().value);
+        self.output_ports[&0].borrow_mut().value = result;
+    }
+
+    fn get_input_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>> {
+        self.input_ports.get(port_id)
+    }
+
+    fn get_output_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>> {
+        self.output_ports.get(port_id)
+    }
+
+    fn get_name(&self) -> &str {
+        "Multiply"
+    }
+
+    fn input_port_number(&self) -> usize {
+        self.input_ports.len()
+    }
+
+    fn output_port_number(&self) -> usize {
+        self.output_ports.len()
+    }
+}
 
 struct OperatorInput {
     output_ports: Vec<Rc<RefCell<Port>>>,
@@ -96,6 +205,18 @@ impl Operator for OperatorInput {
     fn get_output_port(&self, port_id: &usize) -> Option<&Rc<RefCell<Port>>> {
         self.output_ports.get(*port_id)
     }
+
+    fn get_name(&self) -> &str {
+        "Input"
+    }
+
+    fn input_port_number(&self) -> usize {
+        0
+    }
+
+    fn output_port_number(&self) -> usize {
+        self.output_ports.len()
+    }
 }
 
 impl Operator for OperatorOutput {
@@ -110,6 +231,18 @@ impl Operator for OperatorOutput {
     fn get_output_port(&self, _port_id: &usize) -> Option<&Rc<RefCell<Port>>> {
         None
     }
+
+    fn get_name(&self) -> &str {
+        "Output"
+    }
+
+    fn input_port_number(&self) -> usize {
+        self.input_ports.len()
+    }
+
+    fn output_port_number(&self) -> usize {
+        0
+    }
 }
 
 pub struct Connection {
@@ -119,6 +252,10 @@ pub struct Connection {
     pub dst_port_id: usize,
 }
 
+/// Node ids of the track input and output nodes, they can not be removed
+pub const INPUT_NODE_ID: usize = 0;
+pub const OUTPUT_NODE_ID: usize = 1;
+
 pub struct OperatorRack {
     pub operators: HashMap<usize, Box<dyn Operator>>,
     pub connections: Vec<Connection>,
@@ -135,8 +272,8 @@ impl OperatorRack {
         let output_node = Box::new(OperatorOutput::new(track_number));
 
         // 假设我们为它们分配固定的ID，例如0和1
-        rack.add_node(0, input_node);
-        rack.add_node(1, output_node);
+        rack.add_node(INPUT_NODE_ID, input_node);
+        rack.add_node(OUTPUT_NODE_ID, output_node);
 
         rack
     }
@@ -154,24 +291,99 @@ impl OperatorRack {
         });
     }
 
+    pub fn next_node_id(&self) -> usize {
+        self.operators.keys().max().map_or(0, |id| id + 1)
+    }
+
+    /// Removes an operator together with its cables, the input and output nodes are kept
+    pub fn remove_node(&mut self, node_id: usize) -> Result<(), String> {
+        if node_id == INPUT_NODE_ID || node_id == OUTPUT_NODE_ID {
+            return Err(String::from("Input and output nodes can not be removed"));
+        }
+        if self.operators.remove(&node_id).is_none() {
+            return Err(format!("Node {} does not exist", node_id));
+        }
+        self.remove_connections(|c| c.src_node_id == node_id || c.dst_node_id == node_id);
+        Ok(())
+    }
+
+    /// Checked `connect`: both ports have to exist, an input port takes a single cable
+    /// and cables that would close a loop are refused
+    pub fn try_connect(&mut self, src_node_id: usize, src_port_id: usize, dst_node_id: usize, dst_port_id: usize) -> Result<(), String> {
+        let src_exists = self.operators.get(&src_node_id).map_or(false, |node| node.get_output_port(&src_port_id).is_some());
+        let dst_exists = self.operators.get(&dst_node_id).map_or(false, |node| node.get_input_port(&dst_port_id).is_some());
+        if !src_exists || !dst_exists {
+            return Err(String::from("Cables go from an output port to an input port"));
+        }
+        if src_node_id == dst_node_id || self.is_reachable(dst_node_id, src_node_id) {
+            return Err(String::from("Cable would create a loop"));
+        }
+        self.disconnect_port(dst_node_id, dst_port_id, true);
+        self.connect(src_node_id, src_port_id, dst_node_id, dst_port_id);
+        Ok(())
+    }
+
+    /// Removes all cables at a port
+    pub fn disconnect_port(&mut self, node_id: usize, port_id: usize, is_input: bool) {
+        self.remove_connections(|c| if is_input {
+            c.dst_node_id == node_id && c.dst_port_id == port_id
+        } else {
+            c.src_node_id == node_id && c.src_port_id == port_id
+        });
+    }
+
+    /// Removes the matching cables, the input ports they fed go back to 0 instead of keeping the last value
+    fn remove_connections(&mut self, remove: impl Fn(&Connection) -> bool) {
+        let (removed, kept) = self.connections.drain(..).partition(|c| remove(c));
+        self.connections = kept;
+        for connection in removed.iter() {
+            let dst_port = self.operators.get(&connection.dst_node_id)
+                .and_then(|node| node.get_input_port(&connection.dst_port_id));
+            if let Some(dst_port) = dst_port {
+                dst_port.borrow_mut().value = 0;
+            }
+        }
+    }
+
+    fn is_reachable(&self, from_node_id: usize, to_node_id: usize) -> bool {
+        let mut stack = vec![from_node_id];
+        let mut visited = Vec::new();
+        while let Some(node_id) = stack.pop() {
+            if node_id == to_node_id {
+                return true;
+            }
+            if visited.contains(&node_id) {
+                continue;
+            }
+            visited.push(node_id);
+            stack.extend(self.connections.iter().filter(|c| c.src_node_id == node_id).map(|c| c.dst_node_id));
+        }
+        false
+    }
+
+    /// Writes the current track values to the output ports of the input node
+    pub fn set_input_values(&mut self, values: &[i32]) {
+        if let Some(input_node) = self.operators.get(&INPUT_NODE_ID) {
+            for (port_id, value) in values.iter().enumerate() {
+                if let Some(port) = input_node.get_output_port(&port_id) {
+                    port.borrow_mut().value = *value;
+                }
+            }
+        }
+    }
+
     pub fn compute(&mut self) {
         // 根据当前的连接和操作节点，进行拓扑排序
         let sorted_nodes = Self::topological_sort(&self.operators, &self.connections);
-        println!("Sorted Nodes for Computation: {:?}", sorted_nodes);
 
         // 级联更新输入并计算输出
         for node_id in &sorted_nodes {
-            println!("Updating inputs for node {}", node_id);
             // 更新当前节点的输入
             self.update_inputs(*node_id);
 
             // 立即计算当前节点的输出
             if let Some(node) = self.operators.get_mut(node_id) {
-                println!("Computing for node {}", node_id);
                 node.compute();
-                if let Some(output) = node.get_output_port(&0) {
-                    println!("After compute: Node {} Output port 0 value {}", node_id, output.borrow().value);
-                }
             }
         }
     }
@@ -184,8 +396,6 @@ impl OperatorRack {
                     if let Some(dst_node) = self.operators.get_mut(&node_id) {
                         if let Some(dst_port) = dst_node.get_input_port(&connection.dst_port_id) {
                             dst_port.borrow_mut().value = src_port_value;
-                            println!("Value updated: node {} input port {} now {}",
-                                     node_id, connection.dst_port_id, dst_port.borrow().value);
                         }
                     }
                 }
@@ -234,4 +444,91 @@ impl OperatorRack {
             vec![]
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_value(rack: &OperatorRack, port_id: usize) -> i32 {
+        rack.operators[&OUTPUT_NODE_ID].get_input_port(&port_id).unwrap().borrow().value
+    }
+
+    /// Rack with an add node (id 2) summing tracks 0 and 1 into output 0
+    fn add_rack() -> OperatorRack {
+        let mut rack = OperatorRack::new(4);
+        rack.add_node(2, create_operator("add").unwrap());
+        rack.try_connect(INPUT_NODE_ID, 0, 2, 0).unwrap();
+        rack.try_connect(INPUT_NODE_ID, 1, 2, 1).unwrap();
+        rack.try_connect(2, 0, OUTPUT_NODE_ID, 0).unwrap();
+        rack
+    }
+
+    #[test]
+    fn compute_follows_the_cables() {
+        let mut rack = add_rack();
+        rack.add_node(3, create_operator("minus").unwrap());
+        rack.try_connect(2, 0, 3, 0).unwrap();
+        rack.try_connect(INPUT_NODE_ID, 2, 3, 1).unwrap();
+        rack.try_connect(3, 0, OUTPUT_NODE_ID, 1).unwrap();
+        rack.set_input_values(&[3, 4, 10, 0]);
+        rack.compute();
+        assert_eq!(output_value(&rack, 0), 7);
+        assert_eq!(output_value(&rack, 1), -3);
+    }
+
+    #[test]
+    fn loops_are_refused() {
+        let mut rack = add_rack();
+        rack.add_node(3, create_operator("multiply").unwrap());
+        rack.try_connect(2, 0, 3, 0).unwrap();
+        assert!(rack.try_connect(3, 0, 2, 0).is_err());
+        assert!(rack.try_connect(3, 0, 3, 1).is_err());
+        assert_eq!(rack.connections.len(), 4);
+    }
+
+    #[test]
+    fn an_input_port_takes_a_single_cable() {
+        let mut rack = add_rack();
+        rack.try_connect(INPUT_NODE_ID, 2, 2, 1).unwrap();
+        let cables_at_port = rack.connections.iter().filter(|c| c.dst_node_id == 2 && c.dst_port_id == 1).count();
+        assert_eq!(cables_at_port, 1);
+        rack.set_input_values(&[3, 4, 10, 0]);
+        rack.compute();
+        assert_eq!(output_value(&rack, 0), 13);
+    }
+
+    #[test]
+    fn missing_ports_are_refused() {
+        let mut rack = add_rack();
+        assert!(rack.try_connect(INPUT_NODE_ID, 9, 2, 0).is_err());
+        assert!(rack.try_connect(INPUT_NODE_ID, 0, 7, 0).is_err());
+    }
+
+    #[test]
+    fn removed_node_takes_its_cables_and_values() {
+        let mut rack = add_rack();
+        rack.set_input_values(&[3, 4, 0, 0]);
+        rack.compute();
+        assert_eq!(output_value(&rack, 0), 7);
+        rack.remove_node(2).unwrap();
+        assert!(rack.connections.is_empty());
+        rack.compute();
+        assert_eq!(output_value(&rack, 0), 0);
+        assert!(rack.remove_node(2).is_err());
+        assert!(rack.remove_node(INPUT_NODE_ID).is_err());
+    }
+
+    #[test]
+    fn disconnected_input_is_reset() {
+        let mut rack = add_rack();
+        rack.set_input_values(&[3, 4, 0, 0]);
+        rack.compute();
+        rack.disconnect_port(2, 1, true);
+        rack.compute();
+        assert_eq!(output_value(&rack, 0), 3);
+        rack.disconnect_port(INPUT_NODE_ID, 0, false);
+        rack.compute();
+        assert_eq!(output_value(&rack, 0), 0);
+    }
+}
@@ -0,0 +1,185 @@
+use std::f32::consts::PI;
+
+/// Window functions applied before the FFT, see `WindowFunction::coefficient`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowFunction {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+pub const WINDOW_FUNCTIONS: [WindowFunction; 4] = [WindowFunction::Rectangular, WindowFunction::Hann, WindowFunction::Hamming, WindowFunction::Blackman];
+
+impl WindowFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WindowFunction::Rectangular => "Rectangular",
+            WindowFunction::Hann => "Hann",
+            WindowFunction::Hamming => "Hamming",
+            WindowFunction::Blackman => "Blackman",
+        }
+    }
+
+    /// Weight of sample `index` in a window of `length` samples
+    pub fn coefficient(&self, index: usize, length: usize) -> f32 {
+        if length < 2 {
+            return 1.0;
+        }
+        let phase = 2.0 * PI * index as f32 / (length - 1) as f32;
+        match self {
+            WindowFunction::Rectangular => 1.0,
+            WindowFunction::Hann => 0.5 - 0.5 * phase.cos(),
+            WindowFunction::Hamming => 0.54 - 0.46 * phase.cos(),
+            WindowFunction::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+        }
+    }
+}
+
+/// In place radix-2 FFT, the length of `re` and `im` must be a power of two
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    // bit reversed order
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut length = 2;
+    while length <= n {
+        let angle = -2.0 * PI / length as f32;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let (w_re, w_im) = ((angle * k as f32).cos(), (angle * k as f32).sin());
+                let (a, b) = (start + k, start + k + length / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+/// Magnitudes of the bins `0..=fft_size / 2`, a full scale sine (amplitude 1.0) peaks at 1.0.
+/// `samples` are windowed and zero padded to `fft_size`, which must be a power of two.
+pub fn magnitude_spectrum(samples: &[f32], window: WindowFunction, fft_size: usize) -> Vec<f32> {
+    let length = samples.len().min(fft_size);
+    let mut re = vec![0.0; fft_size];
+    let mut im = vec![0.0; fft_size];
+    let mut window_sum = 0.0;
+    for (index, sample) in samples.iter().take(length).enumerate() {
+        let coefficient = window.coefficient(index, length);
+        re[index] = sample * coefficient;
+        window_sum += coefficient;
+    }
+    fft(&mut re, &mut im);
+    // one sided spectrum, the energy of the negative bins is folded onto the positive ones
+    let scale = if window_sum > 0.0 { 2.0 / window_sum } else { 0.0 };
+    (0..=fft_size / 2).map(|bin| {
+        let magnitude = (re[bin] * re[bin] + im[bin] * im[bin]).sqrt() * scale;
+        if bin == 0 || bin == fft_size / 2 { magnitude / 2.0 } else { magnitude }
+    }).collect()
+}
+
+/// Subtracts the mean of `samples`, so a DC offset does not leak into the low bins through the window
+pub fn remove_mean(samples: &mut [f32]) {
+    if samples.is_empty() {
+        return;
+    }
+    let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+    for sample in samples.iter_mut() {
+        *sample -= mean;
+    }
+}
+
+/// Peaks below this magnitude (-120 dB) are treated as silence
+const MIN_PEAK_MAGNITUDE: f32 = 1e-6;
+
+/// Strongest bin above DC as `(frequency, magnitude)`, refined between bins by a parabola through the neighbours.
+/// None for a silent spectrum.
+pub fn peak_frequency(spectrum: &[f32], sample_rate: f32) -> Option<(f32, f32)> {
+    if spectrum.len() < 2 {
+        return None;
+    }
+    let fft_size = (spectrum.len() - 1) * 2;
+    let (bin, &magnitude) = spectrum.iter().enumerate().skip(1)
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+    if magnitude < MIN_PEAK_MAGNITUDE {
+        return None;
+    }
+    let offset = match (spectrum.get(bin - 1), spectrum.get(bin + 1)) {
+        (Some(&left), Some(&right)) => {
+            let denominator = left - 2.0 * magnitude + right;
+            if denominator.abs() > f32::EPSILON { 0.5 * (left - right) / denominator } else { 0.0 }
+        },
+        _ => 0.0,
+    };
+    Some(((bin as f32 + offset) * sample_rate / fft_size as f32, magnitude))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `length` samples of a unit sine with `cycles` periods per `period_length` samples
+    fn sine(length: usize, cycles: f32, period_length: usize) -> Vec<f32> {
+        (0..length).map(|n| (2.0 * PI * cycles * n as f32 / period_length as f32).sin()).collect()
+    }
+
+    #[test]
+    fn bin_centred_sine_peaks_at_its_bin() {
+        let spectrum = magnitude_spectrum(&sine(256, 16.0, 256), WindowFunction::Rectangular, 256);
+        assert_eq!(spectrum.len(), 129);
+        assert!((spectrum[16] - 1.0).abs() < 1e-3, "magnitude {}", spectrum[16]);
+        // with the sample rate equal to the FFT size, the frequency is the bin number
+        let (frequency, magnitude) = peak_frequency(&spectrum, 256.0).unwrap();
+        assert!((frequency - 16.0).abs() < 1e-3, "frequency {}", frequency);
+        assert!((magnitude - 1.0).abs() < 1e-3, "magnitude {}", magnitude);
+    }
+
+    #[test]
+    fn interpolation_recovers_an_off_bin_frequency() {
+        let spectrum = magnitude_spectrum(&sine(256, 20.3, 256), WindowFunction::Hann, 256);
+        let (frequency, _) = peak_frequency(&spectrum, 256.0).unwrap();
+        // closer than the nearest bin, which is 0.3 away
+        assert!((frequency - 20.3).abs() < 0.1, "frequency {}", frequency);
+    }
+
+    #[test]
+    fn short_input_is_zero_padded() {
+        // 100 samples at a frequency that falls onto bin 32 of 256
+        let spectrum = magnitude_spectrum(&sine(100, 32.0, 256), WindowFunction::Rectangular, 256);
+        assert_eq!(spectrum.len(), 129);
+        let (frequency, magnitude) = peak_frequency(&spectrum, 256.0).unwrap();
+        assert!((frequency - 32.0).abs() < 0.5, "frequency {}", frequency);
+        assert!(magnitude > 0.9, "magnitude {}", magnitude);
+    }
+
+    #[test]
+    fn silence_has_no_peak() {
+        let spectrum = magnitude_spectrum(&[0.0; 64], WindowFunction::Hann, 64);
+        assert_eq!(peak_frequency(&spectrum, 64.0), None);
+    }
+
+    #[test]
+    fn dc_offset_has_no_peak() {
+        let mut samples = [0.5; 64];
+        // without the mean removed, the Hann window spreads the offset into bin 1
+        assert!(peak_frequency(&magnitude_spectrum(&samples, WindowFunction::Hann, 64), 64.0).is_some());
+        remove_mean(&mut samples);
+        let spectrum = magnitude_spectrum(&samples, WindowFunction::Hann, 64);
+        assert_eq!(peak_frequency(&spectrum, 64.0), None);
+    }
+}
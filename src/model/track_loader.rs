@@ -17,18 +17,20 @@ pub enum TrackLoader {
 pub struct TrackFileLoader {
     linked_track: Rc<RefCell<Vec<i32>>>,
     is_load: bool,
+    file_path: Option<String>,
 }
 
 impl TrackFileLoader {
     pub fn new (linked_track: Rc<RefCell<Vec<i32>>>) -> Self {
         TrackFileLoader{
             linked_track,
-            is_load:false
+            is_load:false,
+            file_path: None,
         }
     }
 
     pub fn save_track_to_file(&self, file_path: &str) -> io::Result<()> {
-        let file = OpenOptions::new().write(true).create(true).open(file_path)?;
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(file_path)?;
         let mut writer = BufWriter::new(file);
         for &value in self.linked_track.borrow().iter() {
             writer.write_all(&value.to_le_bytes())?;
@@ -48,8 +50,14 @@ impl TrackFileLoader {
         }
         *self.linked_track.borrow_mut() = data;
         self.is_load = true;
+        self.file_path = Some(String::from(file_path));
         Ok(())
     }
+
+    /// Path of the last successfully loaded file
+    pub fn get_file_path(&self) -> Option<&str> {
+        self.file_path.as_deref()
+    }
 }
 
 #[derive(Clone)]
@@ -82,6 +90,18 @@ pub enum WaveGenerateType {
     Noise,
 }
 
+/// Generator settings editable from the view layer
+#[derive(Clone, Debug)]
+pub enum GeneratorParameter {
+    WaveType(WaveGenerateType),
+    Amplitude(i32),
+    Wavelength(usize),
+    /// Radians
+    Phase(f32),
+    Duty(f32),
+    YShift(i32),
+}
+
 #[derive(Clone, Debug)]
 pub struct TrackWaveGenerator{
     linked_track: Rc<RefCell<Vec<i32>>>,
@@ -188,12 +208,13 @@ impl TrackWaveGenerator {
     }
     
     pub fn set_wavelength(&mut self, wavelength:usize) {
-        self.wavelength = wavelength;
+        // a zero wavelength has no samples to play
+        self.wavelength = wavelength.max(1);
         self.generate_wave();
     }
     
-    pub fn set_phase(&mut self, phase: i32) {
-        self.phase = phase as f32;
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase;
         self.generate_wave();
     }
     
@@ -205,6 +226,10 @@ impl TrackWaveGenerator {
             duty = 1.0;
         }
         self.duty = duty;
+        // the rectangular wave takes its duty cycle from the wave type
+        if let WaveGenerateType::Rectangular(duty_cycle) = &mut self.wave_type {
+            *duty_cycle = duty;
+        }
         self.generate_wave();
     }
     
@@ -214,6 +239,49 @@ impl TrackWaveGenerator {
         
     }
 
+    pub fn set_wave_type(&mut self, wave_type: WaveGenerateType) {
+        self.wave_type = match wave_type {
+            WaveGenerateType::Rectangular(_) => WaveGenerateType::Rectangular(self.duty),
+            wave_type => wave_type,
+        };
+        self.generate_wave();
+    }
+
+    pub fn set_parameter(&mut self, parameter: GeneratorParameter) {
+        match parameter {
+            GeneratorParameter::WaveType(wave_type) => self.set_wave_type(wave_type),
+            GeneratorParameter::Amplitude(amplitude) => self.set_amplitude(amplitude),
+            GeneratorParameter::Wavelength(wavelength) => self.set_wavelength(wavelength),
+            GeneratorParameter::Phase(phase) => self.set_phase(phase),
+            GeneratorParameter::Duty(duty) => self.set_duty(duty),
+            GeneratorParameter::YShift(y_shift) => self.set_y_shift(y_shift),
+        }
+    }
+
+    pub fn get_wave_type(&self) -> &WaveGenerateType {
+        &self.wave_type
+    }
+
+    pub fn get_amplitude(&self) -> i32 {
+        self.amplitude as i32
+    }
+
+    pub fn get_wavelength(&self) -> usize {
+        self.wavelength
+    }
+
+    pub fn get_phase(&self) -> f32 {
+        self.phase
+    }
+
+    pub fn get_duty(&self) -> f32 {
+        self.duty
+    }
+
+    pub fn get_y_shift(&self) -> i32 {
+        self.y_shift
+    }
+
     pub fn get_wave_value(&self, time: usize) -> i32 {
         let wave_data = &self.wave_date_buffer;
         let time = time % wave_data.len();
@@ -221,7 +289,11 @@ impl TrackWaveGenerator {
     }
     
     pub fn push_value_to_track(&mut self, time:usize) {
+        let value = self.get_wave_value(time);
         let mut linked_track = self.linked_track.borrow_mut();
-        linked_track[time] = self.get_wave_value(time);
+        if linked_track.len() <= time {
+            linked_track.resize(time + 1, 0);
+        }
+        linked_track[time] = value;
     }
 }
\ No newline at end of file
@@ -5,8 +5,10 @@ use std::io::{self, Write, BufWriter};
 
 pub struct WaveContainer {
     pub wave_track: Vec<Rc<RefCell<Vec<i32>>>>,
+    track_name: Vec<String>,
     track_selection: usize,
     recording_flag: Vec<bool>,
+    mute_flag: Vec<bool>,
     pointer: usize,
     in_out_flag_show: bool,
     in_flag: usize,
@@ -19,8 +21,10 @@ impl WaveContainer {
         let wave_track = (0..track_number).map(|_| Rc::new(RefCell::new(Vec::new()))).collect::<Vec<_>>();
         WaveContainer{
             wave_track,
+            track_name: (0..track_number).map(|i| format!("Track {}", i + 1)).collect(),
             track_selection: 0,
             recording_flag: vec![false; track_number],
+            mute_flag: vec![false; track_number],
             pointer: 0,
             in_out_flag_show: false,
             in_flag: 0,
@@ -33,6 +37,28 @@ impl WaveContainer {
         todo!()
     }
 
+    pub fn set_recording (&mut self, track_index:usize, recording:bool) {
+        self.recording_flag[track_index] = recording;
+    }
+
+    pub fn is_recording (&self, track_index:usize) -> bool {
+        self.recording_flag[track_index]
+    }
+
+    /// Indices of the tracks armed for recording
+    pub fn get_recording_tracks (&self) -> Vec<usize> {
+        self.recording_flag.iter().enumerate().filter(|(_, recording)| **recording).map(|(index, _)| index).collect()
+    }
+
+    /// Muted tracks feed 0 into the operator rack
+    pub fn set_muted (&mut self, track_index:usize, muted:bool) {
+        self.mute_flag[track_index] = muted;
+    }
+
+    pub fn is_muted (&self, track_index:usize) -> bool {
+        self.mute_flag[track_index]
+    }
+
     pub fn mark_in_flag (&mut self) {
         self.in_out_flag_show = true;
         self.in_flag = self.pointer;
@@ -49,7 +75,14 @@ impl WaveContainer {
 
     pub fn copy_in_out_track (&mut self) {
         if self.in_out_flag_show {
-            self.in_out_buffer = self.wave_track[self.track_selection].borrow()[self.in_flag..=self.out_flag].to_vec();
+            let track = self.wave_track[self.track_selection].borrow();
+            if track.is_empty() {
+                return;
+            }
+            // flags may be set in any order and outside of a shorter track
+            let start = self.in_flag.min(self.out_flag).min(track.len() - 1);
+            let end = self.in_flag.max(self.out_flag).min(track.len() - 1);
+            self.in_out_buffer = track[start..=end].to_vec();
         }
     }
 
@@ -64,15 +97,48 @@ impl WaveContainer {
 
     pub fn insert_in_out_track (&mut self) {
         let mut track_to_paste = self.wave_track[self.track_selection].borrow_mut();
-        track_to_paste.splice(self.pointer..self.pointer, self.in_out_buffer.iter().cloned());
+        let pointer = self.pointer.min(track_to_paste.len());
+        track_to_paste.splice(pointer..pointer, self.in_out_buffer.iter().cloned());
+    }
+
+    /// The pointer stays on a sample of the selected track
+    pub fn set_pointer (&mut self, pointer:usize) {
+        let track_length = self.wave_track[self.track_selection].borrow().len();
+        self.pointer = pointer.min(track_length.saturating_sub(1));
+    }
+
+    pub fn get_pointer (&self) -> usize {
+        self.pointer
+    }
+
+    pub fn get_selected_track (&self) -> usize {
+        self.track_selection
+    }
+
+    /// In and out flag positions, `None` while the flags are hidden
+    pub fn get_in_out_flag (&self) -> Option<(usize, usize)> {
+        if self.in_out_flag_show {
+            Some((self.in_flag, self.out_flag))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_track_name (&self, track_index:usize) -> &str {
+        &self.track_name[track_index]
+    }
+
+    pub fn set_track_name (&mut self, track_index:usize, name: &str) {
+        self.track_name[track_index] = name.to_string();
     }
 
     pub fn select_track (&mut self, track_index:usize) {
         self.track_selection = track_index;
+        self.set_pointer(self.pointer);
     }
 
     pub fn save_track_to_file (&self, track_index:usize, file_path: &str) -> io::Result<()> {
-        let file = OpenOptions::new().write(true).create(true).open(file_path)?;
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(file_path)?;
         let mut writer = BufWriter::new(file);
         for &value in self.wave_track[track_index].borrow().iter() {
             writer.write_all(&value.to_le_bytes())?;
@@ -0,0 +1,252 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+use crate::const_parameter::{CORE_SAMPLE_RATE, DEFAULT_DATA_DIRECTORY, DEFAULT_TRACK_LENGTH};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingKind {
+    Bool,
+    Enum(&'static [&'static str]),
+    Range { min: i32, max: i32, step: i32 },
+    Text { max_length: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingValue {
+    Bool(bool),
+    Choice(String),
+    Number(i32),
+    Text(String),
+}
+
+impl SettingValue {
+    /// Value as written to the settings file
+    pub fn to_text(&self) -> String {
+        match self {
+            SettingValue::Bool(value) => value.to_string(),
+            SettingValue::Choice(value) | SettingValue::Text(value) => value.clone(),
+            SettingValue::Number(value) => value.to_string(),
+        }
+    }
+}
+
+pub struct SettingDefinition {
+    /// Name in the settings file
+    pub key: &'static str,
+    pub label: &'static str,
+    pub unit: &'static str,
+    pub kind: SettingKind,
+    pub default: SettingValue,
+}
+
+impl SettingDefinition {
+    fn new(key: &'static str, label: &'static str, unit: &'static str, kind: SettingKind, default: SettingValue) -> Self {
+        SettingDefinition { key, label, unit, kind, default }
+    }
+
+    /// Checks a value against the kind of the setting
+    pub fn validate(&self, value: &SettingValue) -> Result<(), String> {
+        match (&self.kind, value) {
+            (SettingKind::Bool, SettingValue::Bool(_)) => Ok(()),
+            (SettingKind::Enum(options), SettingValue::Choice(choice)) if options.contains(&choice.as_str()) => Ok(()),
+            (SettingKind::Range { min, max, .. }, SettingValue::Number(number)) if number >= min && number <= max => Ok(()),
+            (SettingKind::Text { max_length }, SettingValue::Text(text)) if !text.is_empty() && text.chars().count() <= *max_length => Ok(()),
+            _ => Err(format!("Invalid value '{}' for {}", value.to_text(), self.label)),
+        }
+    }
+
+    /// Reads a value written by `SettingValue::to_text`
+    pub fn parse(&self, text: &str) -> Result<SettingValue, String> {
+        let value = match self.kind {
+            SettingKind::Bool => SettingValue::Bool(text.parse().map_err(|_| format!("{} expects true or false", self.key))?),
+            SettingKind::Enum(_) => SettingValue::Choice(text.to_string()),
+            SettingKind::Range { .. } => SettingValue::Number(text.parse().map_err(|_| format!("{} expects a number", self.key))?),
+            SettingKind::Text { .. } => SettingValue::Text(text.to_string()),
+        };
+        self.validate(&value)?;
+        Ok(value)
+    }
+
+    /// Next value in `dir` for the settings page, text values are edited with the text input
+    pub fn step(&self, value: &SettingValue, dir: i32) -> SettingValue {
+        match (&self.kind, value) {
+            (SettingKind::Bool, SettingValue::Bool(value)) => SettingValue::Bool(!value),
+            (SettingKind::Enum(options), SettingValue::Choice(choice)) => {
+                let current = options.iter().position(|option| option == choice).unwrap_or(0) as i32;
+                let next = (current + dir).rem_euclid(options.len() as i32) as usize;
+                SettingValue::Choice(options[next].to_string())
+            },
+            (SettingKind::Range { min, max, step }, SettingValue::Number(number)) => {
+                SettingValue::Number((number + dir * step).clamp(*min, *max))
+            },
+            _ => value.clone(),
+        }
+    }
+}
+
+pub const THEMES: [&str; 2] = ["Dark", "Light"];
+
+/// All settings in the order of the settings page
+fn schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new("brightness", "Brightness", "%", SettingKind::Range { min: 10, max: 100, step: 10 }, SettingValue::Number(100)),
+        SettingDefinition::new("theme", "Theme", "", SettingKind::Enum(&THEMES), SettingValue::Choice(String::from("Dark"))),
+        SettingDefinition::new("track_length", "Track Length", "smp", SettingKind::Range { min: 60, max: 4800, step: 60 },
+                               SettingValue::Number(DEFAULT_TRACK_LENGTH as i32)),
+        SettingDefinition::new("sample_rate", "Sample Rate", "Hz", SettingKind::Range { min: 10, max: 120, step: 10 },
+                               SettingValue::Number(CORE_SAMPLE_RATE as i32)),
+        SettingDefinition::new("key_repeat", "Key Repeat", "", SettingKind::Bool, SettingValue::Bool(false)),
+        SettingDefinition::new("key_repeat_delay", "Repeat Delay", "ms", SettingKind::Range { min: 100, max: 1000, step: 50 }, SettingValue::Number(400)),
+        SettingDefinition::new("data_directory", "Data Directory", "", SettingKind::Text { max_length: 32 },
+                               SettingValue::Text(String::from(DEFAULT_DATA_DIRECTORY))),
+    ]
+}
+
+/// # Settings
+/// Typed values described by the settings schema, stored as `key = value` lines:
+/// ```text
+/// # settings file
+/// brightness = 80
+/// theme = Dark
+/// key_repeat = true
+/// ```
+/// Unknown keys and invalid values are skipped with a warning and keep their default.
+/// Overrides (e.g. from command line flags) take precedence over the stored values but are never saved.
+pub struct Settings {
+    definitions: Vec<SettingDefinition>,
+    values: Vec<SettingValue>,
+    overrides: Vec<Option<SettingValue>>,
+    file_path: Option<PathBuf>,
+    changed: bool,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        let definitions = schema();
+        let values = definitions.iter().map(|definition| definition.default.clone()).collect();
+        let overrides = definitions.iter().map(|_| None).collect();
+        Settings {
+            definitions,
+            values,
+            overrides,
+            file_path: None,
+            changed: true,
+        }
+    }
+
+    /// Loads the settings file, a missing file gives the defaults and is created on the first change
+    pub fn load(file_path: &Path) -> Self {
+        let mut settings = Settings::new();
+        settings.file_path = Some(file_path.to_path_buf());
+        match fs::read_to_string(file_path) {
+            Ok(content) => {
+                settings.parse(&content);
+                info!("Settings loaded from {}", file_path.display());
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => info!("No settings file {}, using defaults", file_path.display()),
+            Err(e) => warn!("Unable to read settings file {}: {}", file_path.display(), e),
+        }
+        settings
+    }
+
+    fn parse(&mut self, content: &str) {
+        for (line_index, line) in content.lines().enumerate() {
+            // only whole lines are comments, '#' may be part of a value
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let result = line.split_once('=')
+                .ok_or_else(|| format!("expected 'key = value', got '{}'", line))
+                .and_then(|(key, value)| self.set_text(key.trim(), value.trim()));
+            if let Err(e) = result {
+                warn!("Settings line {}: {}", line_index + 1, e);
+            }
+        }
+    }
+
+    fn set_text(&mut self, key: &str, text: &str) -> Result<(), String> {
+        let index = self.index(key).ok_or_else(|| format!("unknown setting {}", key))?;
+        self.values[index] = self.definitions[index].parse(text)?;
+        Ok(())
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let file_path = match &self.file_path {
+            Some(file_path) => file_path,
+            None => return Ok(()),
+        };
+        let mut content = String::new();
+        for (definition, value) in self.definitions.iter().zip(self.values.iter()) {
+            content.push_str(&format!("{} = {}\n", definition.key, value.to_text()));
+        }
+        fs::write(file_path, content)
+    }
+
+    pub fn definitions(&self) -> &[SettingDefinition] {
+        &self.definitions
+    }
+
+    fn index(&self, key: &str) -> Option<usize> {
+        self.definitions.iter().position(|definition| definition.key == key)
+    }
+
+    /// The override if there is one, the stored value otherwise
+    pub fn value(&self, index: usize) -> &SettingValue {
+        self.overrides[index].as_ref().unwrap_or(&self.values[index])
+    }
+
+    /// Stores a value, it replaces any override of the setting
+    pub fn set_value(&mut self, index: usize, value: SettingValue) -> Result<(), String> {
+        self.definitions[index].validate(&value)?;
+        if self.value(index) != &value {
+            self.changed = true;
+        }
+        self.values[index] = value;
+        self.overrides[index] = None;
+        Ok(())
+    }
+
+    /// Overrides a value by its key for this run only, e.g. from a command line flag.
+    /// Text overrides are not limited to the length the settings page can edit.
+    pub fn set_override(&mut self, key: &str, value: SettingValue) -> Result<(), String> {
+        let index = self.index(key).ok_or_else(|| format!("Unknown setting {}", key))?;
+        match (&self.definitions[index].kind, &value) {
+            (SettingKind::Text { .. }, SettingValue::Text(text)) if !text.is_empty() => {},
+            _ => self.definitions[index].validate(&value)?,
+        }
+        self.overrides[index] = Some(value);
+        self.changed = true;
+        Ok(())
+    }
+
+    /// True once after any value changed, the view applies the settings then
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn get(&self, key: &str) -> &SettingValue {
+        // only called with keys of the schema
+        self.value(self.index(key).expect("Unknown setting"))
+    }
+
+    pub fn get_bool(&self, key: &str) -> bool {
+        matches!(self.get(key), SettingValue::Bool(true))
+    }
+
+    pub fn get_number(&self, key: &str) -> i32 {
+        match self.get(key) {
+            SettingValue::Number(number) => *number,
+            _ => 0,
+        }
+    }
+
+    /// Choice of an enum setting or the text of a text setting
+    pub fn get_text(&self, key: &str) -> String {
+        match self.get(key) {
+            SettingValue::Choice(text) | SettingValue::Text(text) => text.clone(),
+            value => value.to_text(),
+        }
+    }
+}
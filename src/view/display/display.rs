@@ -2,6 +2,7 @@ use crate::view::display::font::{font_pixel_operator_16, font_dot_digital_20};
 use image::GenericImageView;
 use image::io::Reader as ImageReader;
 use std::path::Path;
+use std::{fs, io};
 
 #[cfg(windows)]
 use minifb::{Window, WindowOptions};
@@ -9,12 +10,27 @@ use minifb::{Window, WindowOptions};
 #[cfg(all(target_os = "linux", target_arch = "arm"))]
 use framebuffer::Framebuffer;
 
+/// Backlight devices of the panel
+const BACKLIGHT_DIRECTORY: &str = "/sys/class/backlight";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisplayTheme {
+    Dark,
+    /// Colors inverted when the frame is sent to the screen
+    Light,
+}
+
 pub struct Display {
     width: usize,
     height: usize,
     line_byte_length: usize,
     bytes_per_pixel: usize,
     buffer: Vec<u8>,
+    /// Drawing area `[x0, y0, x1, y1]`, exclusive end, coordinates are relative to its origin
+    viewport: [usize; 4],
+    /// Set by every drawing call, read with `take_dirty`
+    dirty: bool,
+    theme: DisplayTheme,
     #[cfg(windows)]
     window_win:Window,
     #[cfg(windows)]
@@ -35,6 +51,9 @@ impl Display {
             line_byte_length,
             bytes_per_pixel,
             buffer,
+            viewport: [0, 0, width, height],
+            dirty: false,
+            theme: DisplayTheme::Dark,
 
             #[cfg(windows)]
             window_win: Window::new(
@@ -58,15 +77,75 @@ impl Display {
         // self.buffer.fill(0);
     }
     
+    /// Clears the viewport
     pub fn clean (&mut self) {
-        self.buffer.fill(0);
+        self.dirty = true;
+        let [x0, y0, x1, y1] = self.viewport;
+        if self.viewport == [0, 0, self.width, self.height] {
+            self.buffer.fill(0);
+            return;
+        }
+        for y in y0..y1 {
+            let start = (y * self.width + x0) * self.bytes_per_pixel;
+            let end = (y * self.width + x1) * self.bytes_per_pixel;
+            self.buffer[start..end].fill(0);
+        }
+    }
+
+    /// Restricts drawing to `rect` and moves the origin to its top left corner
+    pub fn set_viewport (&mut self, rect: [usize; 4]) {
+        self.viewport = [rect[0].min(self.width), rect[1].min(self.height), rect[2].min(self.width), rect[3].min(self.height)];
+    }
+
+    pub fn reset_viewport (&mut self) {
+        self.viewport = [0, 0, self.width, self.height];
+    }
+
+    pub fn set_theme (&mut self, theme: DisplayTheme) {
+        self.theme = theme;
+    }
+
+    /// Backlight in percent, written to the first backlight device
+    pub fn set_brightness (&mut self, percent: u32) -> io::Result<()> {
+        let device = fs::read_dir(BACKLIGHT_DIRECTORY)?
+            .filter_map(|entry| entry.ok())
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no backlight device"))?
+            .path();
+        let max_brightness: u32 = fs::read_to_string(device.join("max_brightness"))?
+            .trim()
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid max_brightness"))?;
+        fs::write(device.join("brightness"), (max_brightness * percent.min(100) / 100).to_string())
+    }
+
+    fn invert_buffer (&mut self) {
+        for pixel in self.buffer.chunks_mut(self.bytes_per_pixel) {
+            for channel in pixel.iter_mut().take(3) {
+                *channel = !*channel;
+            }
+        }
+    }
+
+    /// True when anything was drawn since the last call
+    pub fn take_dirty (&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Width and height of the viewport
+    pub fn viewport_size (&self) -> [usize; 2] {
+        [self.viewport[2] - self.viewport[0], self.viewport[3] - self.viewport[1]]
     }
 
     ///
     /// frame processing
     ///
+    /// All drawing goes through here, pixels outside the viewport are skipped
     pub fn set_pixel_color (&mut self, x:usize, y:usize, color:(u8,u8,u8)) {
-        if x < self.width && y < self.height {
+        let x = x + self.viewport[0];
+        let y = y + self.viewport[1];
+        if x < self.viewport[2] && y < self.viewport[3] {
+            self.dirty = true;
             let index = (y * self.width + x) * self.bytes_per_pixel;
             self.buffer[index + 2] = color.0;     // Red
             self.buffer[index + 1] = color.1; // Green
@@ -106,10 +185,7 @@ impl Display {
 
         loop {
             // Set pixel color, skip Alpha channel
-            let index = (y as usize * self.width + x as usize) * self.bytes_per_pixel;
-            self.buffer[index + 2] = color.0;     // Red
-            self.buffer[index + 1] = color.1; // Green
-            self.buffer[index] = color.2; // Blue
+            self.set_pixel_color(x as usize, y as usize, color);
 
             if x == x1 as isize && y == y1 as isize { break; }
             let e2 = 2 * err;
@@ -132,38 +208,20 @@ impl Display {
         if fill {
             for y in y0..=y1 {
                 for x in x0..=x1 {
-                    let index = (y * self.width + x) * self.bytes_per_pixel;
-                    self.buffer[index + 2] = color.0;     // Red
-                    self.buffer[index + 1] = color.1; // Green
-                    self.buffer[index] = color.2; // Blue
+                    self.set_pixel_color(x, y, color);
                 }
             }
         } else {
             // draw top and bottom
             for x in x0..=x1 {
-                let top_index = (y0 * self.width + x) * self.bytes_per_pixel;
-                let bottom_index = (y1 * self.width + x) * self.bytes_per_pixel;
-
-                self.buffer[top_index + 2] = color.0;     // Red
-                self.buffer[top_index + 1] = color.1; // Green
-                self.buffer[top_index] = color.2; // Blue
-
-                self.buffer[bottom_index + 2] = color.0;     // Red
-                self.buffer[bottom_index + 1] = color.1; // Green
-                self.buffer[bottom_index ] = color.2; // Blue
+                self.set_pixel_color(x, y0, color);
+                self.set_pixel_color(x, y1, color);
             }
 
             // draw left and right, and skip conner
             for y in (y0 + 1)..y1 {
-                let left_index = (y * self.width + x0) * self.bytes_per_pixel;
-                let right_index = (y * self.width + x1) * self.bytes_per_pixel;
-                self.buffer[left_index + 2] = color.0;     // Red
-                self.buffer[left_index + 1] = color.1; // Green
-                self.buffer[left_index] = color.2; // Blue
-
-                self.buffer[right_index + 2] = color.0;     // Red
-                self.buffer[right_index + 1] = color.1; // Green
-                self.buffer[right_index] = color.2; // Blue
+                self.set_pixel_color(x0, y, color);
+                self.set_pixel_color(x1, y, color);
             }
         }
     }
@@ -268,11 +326,8 @@ impl Display {
                 for x in (cx as isize - radius as isize)..=(cx as isize + radius as isize) {
                     let dx = x - cx as isize;
                     let dy = y - cy as isize;
-                    if dx*dx + dy*dy <= (radius as isize)*(radius as isize) {
-                        let index = (y as usize * self.width + x as usize) * self.bytes_per_pixel;
-                        self.buffer[index + 2] = color.0;     // Red
-                        self.buffer[index + 1] = color.1; // Green
-                        self.buffer[index] = color.2; // Blue
+                    if dx*dx + dy*dy <= (radius as isize)*(radius as isize) && x >= 0 && y >= 0 {
+                        self.set_pixel_color(x as usize, y as usize, color);
                     }
                 }
             }
@@ -294,11 +349,8 @@ impl Display {
                 ];
 
                 for &(px, py) in &points {
-                    if px >= 0 && px < self.width as isize && py >= 0 && py < self.height as isize {
-                        let index = (py as usize * self.width + px as usize) * self.bytes_per_pixel;
-                        self.buffer[index + 2] = color.0;     // Red
-                        self.buffer[index + 1] = color.1; // Green
-                        self.buffer[index] = color.2; // Blue
+                    if px >= 0 && py >= 0 {
+                        self.set_pixel_color(px as usize, py as usize, color);
                     }
                 }
 
@@ -312,6 +364,7 @@ impl Display {
         }
     }
 
+    /// Characters without a glyph (non-ASCII, control characters) are drawn as `?`
     pub fn text(&mut self, text: &str, font:usize, x: usize, y: usize, scale: usize, spacing: usize, color: (u8, u8, u8)) {
         let mut x = x;
         let (glyphs_table, font_height) = match font {
@@ -320,7 +373,7 @@ impl Display {
             _ => (font_pixel_operator_16::FONT_LOOKUP_TABLE, font_pixel_operator_16::FONT_HEIGHT),
         };
         for c in text.chars() {
-            let char_pixels = glyphs_table[c as usize - 32];
+            let char_pixels = glyphs_table[glyph_index(c)];
             let char_width = char_pixels.len() / font_height;
             if scale == 1 {
                 // 如果scale为1，直接渲染，不进行放大
@@ -328,10 +381,7 @@ impl Display {
                     for col in 0..char_width {
                         let pixel_index = row * char_width + col; // 在char_pixels中的索引
                         if char_pixels[pixel_index] == 1 {
-                            let offset = ((y + row) * self.width + x + col) * self.bytes_per_pixel;
-                            self.buffer[offset + 2] = color.0; // R
-                            self.buffer[offset + 1] = color.1; // G
-                            self.buffer[offset] = color.2; // B
+                            self.set_pixel_color(x + col, y + row, color);
                         }
                     }
                 }
@@ -343,10 +393,7 @@ impl Display {
                         if char_pixels[pixel_index] == 1 {
                             for dy in 0..scale {
                                 for dx in 0..scale {
-                                    let offset = ((y + row * scale + dy) * self.width + x + col * scale + dx) * self.bytes_per_pixel;
-                                    self.buffer[offset + 2] = color.0; // R
-                                    self.buffer[offset + 1] = color.1; // G
-                                    self.buffer[offset] = color.2; // B
+                                    self.set_pixel_color(x + col * scale + dx, y + row * scale + dy, color);
                                 }
                             }
                         }
@@ -358,6 +405,17 @@ impl Display {
         }
     }
 
+    pub fn text_width(&self, text: &str, font:usize, scale: usize, spacing: usize) -> usize {
+        let (glyphs_table, font_height) = match font {
+            1 => (font_pixel_operator_16::FONT_LOOKUP_TABLE, font_pixel_operator_16::FONT_HEIGHT),
+            2 => (font_dot_digital_20::FONT_LOOKUP_TABLE, font_dot_digital_20::FONT_HEIGHT),
+            _ => (font_pixel_operator_16::FONT_LOOKUP_TABLE, font_pixel_operator_16::FONT_HEIGHT),
+        };
+        text.chars()
+            .map(|c| scale * glyphs_table[glyph_index(c)].len() / font_height + spacing)
+            .sum()
+    }
+
     pub fn image<P: AsRef<Path>>(&mut self, img_path:P, start_x:usize, start_y:usize) {
         let img = ImageReader::open(img_path).unwrap().decode().unwrap();
         let (img_width, img_height) = img.dimensions();
@@ -367,21 +425,12 @@ impl Display {
                 let dst_x = start_x + x as usize;
                 let dst_y = start_y + y as usize;
 
-                if dst_x >= self.width || dst_y >= self.height {
-                    continue;
-                }
-
                 let pixel = img.get_pixel(x, y).0;
                 if pixel[3] == 0 {
                     continue;
                 }
 
-                let idx = (dst_y * self.width + dst_x) * 4;
-                if idx + 3 < self.buffer.len() {
-                    self.buffer[idx] = pixel[2];    // B
-                    self.buffer[idx + 1] = pixel[1];  // G
-                    self.buffer[idx + 2] = pixel[0];  // R
-                }
+                self.set_pixel_color(dst_x, dst_y, (pixel[0], pixel[1], pixel[2]));
             }
         }
     }
@@ -392,6 +441,11 @@ impl Display {
     /// Frame end for updating
     ///
     pub fn frame_update (&mut self) {
+        // the light theme inverts only what is sent, drawing code keeps its dark colors
+        let inverted = self.theme == DisplayTheme::Light;
+        if inverted {
+            self.invert_buffer();
+        }
 
         #[cfg(windows)]
         {
@@ -408,5 +462,17 @@ impl Display {
 
         #[cfg(all(target_os = "linux", target_arch = "arm"))]
         self.window_linux.write_frame(&self.buffer);
+
+        if inverted {
+            self.invert_buffer();
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Index into the font lookup tables, which cover the printable ASCII range
+fn glyph_index(c: char) -> usize {
+    match c {
+        ' '..='~' => c as usize - 32,
+        _ => '?' as usize - 32,
+    }
+}
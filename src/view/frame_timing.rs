@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use crate::view::display::display::Display;
+
+/// Frames kept for the percentiles
+const FRAME_HISTORY: usize = 240;
+/// Lowest frame rate the pacer drops to under load
+const MIN_LOAD_FPS: f32 = 10.0;
+/// Frame rate while nothing on screen changes, the core still runs at its own rate
+const IDLE_FPS: f32 = 10.0;
+/// Frames without a change before the idle frame rate is used
+const IDLE_FRAMES: usize = 30;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameSample {
+    /// Input, core update and drawing
+    pub render: Duration,
+    /// Copying the buffer to the screen
+    pub flush: Duration,
+}
+
+impl FrameSample {
+    pub fn total(&self) -> Duration {
+        self.render + self.flush
+    }
+}
+
+/// # Frame Statistics
+/// Render and flush time of the recent frames and the frames that took longer than their budget.
+pub struct FrameStats {
+    samples: VecDeque<FrameSample>,
+    frame_count: u64,
+    overrun_count: u64,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        FrameStats {
+            samples: VecDeque::with_capacity(FRAME_HISTORY),
+            frame_count: 0,
+            overrun_count: 0,
+        }
+    }
+
+    pub fn record(&mut self, render: Duration, flush: Duration, budget: Duration) {
+        if self.samples.len() == FRAME_HISTORY {
+            self.samples.pop_front();
+        }
+        let sample = FrameSample { render, flush };
+        if sample.total() > budget {
+            self.overrun_count += 1;
+        }
+        self.samples.push_back(sample);
+        self.frame_count += 1;
+    }
+
+    pub fn last(&self) -> Option<FrameSample> {
+        self.samples.back().copied()
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn overrun_count(&self) -> u64 {
+        self.overrun_count
+    }
+
+    /// Frame time below which `percentile` percent of the recent frames stayed
+    pub fn percentile(&self, percentile: f32) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut totals: Vec<Duration> = self.samples.iter().map(|sample| sample.total()).collect();
+        totals.sort();
+        let index = (percentile.clamp(0.0, 100.0) / 100.0 * (totals.len() - 1) as f32).round() as usize;
+        totals[index]
+    }
+
+    pub fn p50(&self) -> Duration {
+        self.percentile(50.0)
+    }
+
+    pub fn p99(&self) -> Duration {
+        self.percentile(99.0)
+    }
+
+    pub fn summary(&self) -> String {
+        format!("{} frames, {} over time, p50 {:.1}ms, p99 {:.1}ms",
+                self.frame_count(), self.overrun_count(), duration_ms(self.p50()), duration_ms(self.p99()))
+    }
+
+    pub fn draw_overlay(&self, display: &mut Display, rect: [usize; 4], frame_interval: Duration) {
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (0, 0, 0), true);
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (60, 60, 60), false);
+        let last = self.last().unwrap_or_default();
+        let lines = [
+            format!("Render {:.1}ms Flush {:.1}ms", duration_ms(last.render), duration_ms(last.flush)),
+            format!("p50 {:.1}ms p99 {:.1}ms", duration_ms(self.p50()), duration_ms(self.p99())),
+            format!("Over {}/{}  {:.0} FPS", self.overrun_count(), self.frame_count(), 1.0 / frame_interval.as_secs_f32()),
+        ];
+        for (index, line) in lines.iter().enumerate() {
+            display.text(line, 1, rect[0] + 5, rect[1] + 3 + index * 17, 1, 1, (200, 200, 200));
+        }
+    }
+}
+
+fn duration_ms(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+/// # Frame Pacer
+/// Lowers the UI frame rate while frames do not fit into their interval, and while nothing
+/// on screen changes. Back at the configured rate as soon as frames are fast and the screen changes.
+pub struct FramePacer {
+    base_interval: Duration,
+    load_interval: Duration,
+    idle_frames: usize,
+}
+
+impl FramePacer {
+    pub fn new(base_interval: Duration) -> Self {
+        FramePacer {
+            base_interval,
+            load_interval: base_interval,
+            idle_frames: 0,
+        }
+    }
+
+    /// `changed` tells whether the frame drew anything
+    pub fn update(&mut self, frame_time: Duration, changed: bool) {
+        self.idle_frames = if changed { 0 } else { self.idle_frames.saturating_add(1) };
+        let max_interval = Duration::from_secs_f32(1.0 / MIN_LOAD_FPS).max(self.base_interval);
+        if frame_time > self.load_interval {
+            self.load_interval = self.load_interval.mul_f32(1.25).min(max_interval);
+        } else if frame_time < self.load_interval / 2 {
+            self.load_interval = self.load_interval.mul_f32(0.9).max(self.base_interval);
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle_frames >= IDLE_FRAMES
+    }
+
+    pub fn frame_interval(&self) -> Duration {
+        if self.is_idle() {
+            self.load_interval.max(Duration::from_secs_f32(1.0 / IDLE_FPS))
+        } else {
+            self.load_interval
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::{Duration, Instant};
+
+/// # Chord Detector
+/// Turns logical actions pressed together within the chord window into a single chord action.
+/// Chord keys are pressed in the order they are written, e.g. hold `Menu` then press `Left`.
+/// Only a press that can start a chord is held back until the chord completes, the key is
+/// released or the window runs out; all other presses are delivered at once.
+/// The individual presses of a completed chord are suppressed.
+pub struct ChordDetector {
+    chords: Vec<(Vec<String>, String)>,
+    chord_window: Duration,
+    pending: Vec<(String, Instant)>,
+}
+
+impl ChordDetector {
+    pub fn new(chords: Vec<(Vec<String>, String)>, chord_window: Duration) -> Self {
+        ChordDetector {
+            chords,
+            chord_window,
+            pending: Vec::new(),
+        }
+    }
+
+    /// `pressed` are the actions newly pressed in this frame, `held` all actions currently held
+    pub fn process(&mut self, mut pressed: Vec<String>, held: &[String], now: Instant) -> Vec<String> {
+        let mut output = Vec::new();
+        // keys that start a chord first, for chords pressed within a single frame
+        pressed.sort_by_key(|action| !self.is_chord_prefix(&[action.clone()]));
+
+        for action in pressed {
+            // a repeat of a held back key, e.g. by key repeat, is not a second chord key
+            if self.pending.iter().any(|(a, _)| *a == action) {
+                continue;
+            }
+            let mut candidate: Vec<String> = self.pending.iter().map(|(a, _)| a.clone()).collect();
+            candidate.push(action.clone());
+
+            if let Some(chord_action) = self.find_chord(&candidate) {
+                output.push(chord_action);
+                self.pending.clear();
+            } else if self.is_chord_prefix(&candidate) {
+                self.pending.push((action, now));
+            } else {
+                // not part of a chord with the pending keys, release them as single presses
+                output.extend(self.pending.drain(..).map(|(a, _)| a));
+                if self.is_chord_prefix(&[action.clone()]) {
+                    self.pending.push((action, now));
+                } else {
+                    output.push(action);
+                }
+            }
+        }
+
+        // a pending press resolves to a single press when released or when the window runs out
+        let window_expired = self.pending.first()
+            .map(|(_, pressed_at)| now.duration_since(*pressed_at) >= self.chord_window)
+            .unwrap_or(false);
+        let released = self.pending.iter().any(|(a, _)| !held.contains(a));
+        if window_expired || released {
+            output.extend(self.pending.drain(..).map(|(a, _)| a));
+        }
+
+        output
+    }
+
+    fn find_chord(&self, actions: &[String]) -> Option<String> {
+        self.chords.iter()
+            .find(|(keys, _)| keys.len() == actions.len() && keys.iter().all(|k| actions.contains(k)))
+            .map(|(_, chord_action)| chord_action.clone())
+    }
+
+    /// True when the actions are the leading keys of a longer chord
+    fn is_chord_prefix(&self, actions: &[String]) -> bool {
+        self.chords.iter()
+            .any(|(keys, _)| keys.len() > actions.len() && actions.iter().all(|a| keys[..actions.len()].contains(a)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    fn detector() -> ChordDetector {
+        ChordDetector::new(vec![(actions(&["Menu", "Left"]), String::from("MarkIn"))], Duration::from_millis(200))
+    }
+
+    #[test]
+    fn chord_is_reported_once() {
+        let mut detector = detector();
+        let start = Instant::now();
+        assert!(detector.process(actions(&["Menu"]), &actions(&["Menu"]), start).is_empty());
+        let output = detector.process(actions(&["Left"]), &actions(&["Menu", "Left"]), start + Duration::from_millis(50));
+        assert_eq!(output, actions(&["MarkIn"]));
+        assert!(detector.process(Vec::new(), &[], start + Duration::from_millis(300)).is_empty());
+    }
+
+    #[test]
+    fn chord_pressed_in_one_frame() {
+        let mut detector = detector();
+        let output = detector.process(actions(&["Left", "Menu"]), &actions(&["Menu", "Left"]), Instant::now());
+        assert_eq!(output, actions(&["MarkIn"]));
+    }
+
+    #[test]
+    fn expired_window_releases_the_single_press() {
+        let mut detector = detector();
+        let start = Instant::now();
+        assert!(detector.process(actions(&["Menu"]), &actions(&["Menu"]), start).is_empty());
+        let output = detector.process(Vec::new(), &actions(&["Menu"]), start + Duration::from_millis(200));
+        assert_eq!(output, actions(&["Menu"]));
+        // the second key after the window is a single press of its own
+        let output = detector.process(actions(&["Left"]), &actions(&["Menu", "Left"]), start + Duration::from_millis(250));
+        assert_eq!(output, actions(&["Left"]));
+    }
+
+    #[test]
+    fn released_key_is_delivered() {
+        let mut detector = detector();
+        let start = Instant::now();
+        assert!(detector.process(actions(&["Menu"]), &actions(&["Menu"]), start).is_empty());
+        assert_eq!(detector.process(Vec::new(), &[], start + Duration::from_millis(20)), actions(&["Menu"]));
+    }
+
+    #[test]
+    fn non_chord_key_is_delivered_at_once() {
+        let mut detector = detector();
+        assert_eq!(detector.process(actions(&["Up"]), &actions(&["Up"]), Instant::now()), actions(&["Up"]));
+        // a non-chord key also releases a held back press
+        let start = Instant::now();
+        assert!(detector.process(actions(&["Menu"]), &actions(&["Menu"]), start).is_empty());
+        let output = detector.process(actions(&["Up"]), &actions(&["Menu", "Up"]), start + Duration::from_millis(50));
+        assert_eq!(output, actions(&["Menu", "Up"]));
+    }
+
+    #[test]
+    fn repeated_key_does_not_complete_a_chord() {
+        let mut detector = detector();
+        let start = Instant::now();
+        let held = actions(&["Menu"]);
+        assert_eq!(detector.process(actions(&["Menu"]), &held, start), Vec::<String>::new());
+        assert_eq!(detector.process(actions(&["Menu"]), &held, start + Duration::from_millis(100)), Vec::<String>::new());
+        assert_eq!(detector.process(Vec::new(), &held, start + Duration::from_millis(200)), actions(&["Menu"]));
+        // repeats after the window run out are single presses again, never the chord
+        let mut output = Vec::new();
+        for step in 0..4 {
+            let now = start + Duration::from_millis(300 + step * 100);
+            output.extend(detector.process(actions(&["Menu"]), &held, now));
+        }
+        output.extend(detector.process(Vec::new(), &[], start + Duration::from_millis(800)));
+        assert!(output.iter().all(|action| action == "Menu"), "{:?}", output);
+        assert!(!output.is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+use std::io;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use crate::view::interaction::key_manager::KeyManager;
+use crate::view::interaction::key_map::KeyMap;
+use crate::view::interaction::chord_detector::ChordDetector;
+use crate::view::interaction::input_record::{InputRecorder, InputReplayer};
+use crate::view::interaction::touch_manager::{LongPressDetector, TouchEvent, TouchManager};
+use crate::view::interaction::script_input::ScriptInput;
+use crate::const_parameter::{DISPLAY_WIDTH, DISPLAY_HEIGHT};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    Key(String),
+    Touch(TouchEvent),
+}
+
+impl InputEvent {
+    pub fn to_record(&self) -> String {
+        match self {
+            InputEvent::Key(label) => format!("key {}", label),
+            InputEvent::Touch(touch_event) => {
+                let kind = match touch_event {
+                    TouchEvent::Down { .. } => "down",
+                    TouchEvent::Move { .. } => "move",
+                    TouchEvent::Up { .. } => "up",
+                    TouchEvent::Tap { .. } => "tap",
+                };
+                let [x, y] = touch_event.position();
+                format!("touch {} {} {}", kind, x, y)
+            },
+        }
+    }
+
+    pub fn from_record(record: &str) -> Option<Self> {
+        let mut parts = record.split_whitespace();
+        match parts.next()? {
+            "key" => Some(InputEvent::Key(parts.next()?.to_string())),
+            "touch" => {
+                let kind = parts.next()?;
+                let x = parts.next()?.parse().ok()?;
+                let y = parts.next()?.parse().ok()?;
+                let touch_event = match kind {
+                    "down" => TouchEvent::Down { x, y },
+                    "move" => TouchEvent::Move { x, y },
+                    "up" => TouchEvent::Up { x, y },
+                    "tap" => TouchEvent::Tap { x, y },
+                    _ => return None,
+                };
+                Some(InputEvent::Touch(touch_event))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// # Input Manager
+/// Collects the input events of one frame, either from the `KeyManager` or from a replay file,
+/// plus any touch panel and script input, and optionally records them with their frame number.
+/// Physical keys are translated to logical actions with the `KeyMap` of the current page.
+/// A long touch press is reported as the `Help` action.
+pub struct InputManager {
+    key_manager: Option<KeyManager>,
+    key_map: KeyMap,
+    chord_detector: ChordDetector,
+    page_id: String,
+    touch_manager: Option<TouchManager>,
+    long_press: LongPressDetector,
+    script_input: Option<ScriptInput>,
+    recorder: Option<InputRecorder>,
+    replayer: Option<InputReplayer>,
+    frame_count: u64,
+    frame_events: Vec<InputEvent>,
+    /// Screen area the touches are reported in, as `[x0, y0, x1, y1]` with exclusive end
+    touch_viewport: [usize; 4],
+}
+
+impl InputManager {
+    pub fn new() -> Self {
+        let key_map = KeyMap::new();
+        InputManager {
+            key_manager: Some(KeyManager::new()),
+            chord_detector: ChordDetector::new(key_map.chords.clone(), key_map.chord_window),
+            key_map,
+            page_id: String::new(),
+            touch_manager: None,
+            long_press: LongPressDetector::new(),
+            script_input: None,
+            recorder: None,
+            replayer: None,
+            frame_count: 0,
+            frame_events: Vec::new(),
+            touch_viewport: [0, 0, DISPLAY_WIDTH, DISPLAY_HEIGHT],
+        }
+    }
+
+    /// Without keyboard device, input only comes from script, touch or replay sources
+    pub fn new_headless() -> Self {
+        let key_map = KeyMap::new();
+        InputManager {
+            key_manager: None,
+            chord_detector: ChordDetector::new(Vec::new(), key_map.chord_window),
+            key_map,
+            page_id: String::new(),
+            touch_manager: None,
+            long_press: LongPressDetector::new(),
+            script_input: None,
+            recorder: None,
+            replayer: None,
+            frame_count: 0,
+            frame_events: Vec::new(),
+            touch_viewport: [0, 0, DISPLAY_WIDTH, DISPLAY_HEIGHT],
+        }
+    }
+
+    /// Replays a recorded input file, the `KeyManager` is bypassed entirely
+    pub fn from_replay(file_path: &str) -> io::Result<Self> {
+        let replayer = InputReplayer::new(file_path)?;
+        info!("Replaying input from {}", file_path);
+        let key_map = KeyMap::new();
+        Ok(InputManager {
+            key_manager: None,
+            chord_detector: ChordDetector::new(Vec::new(), key_map.chord_window),
+            key_map,
+            page_id: String::new(),
+            touch_manager: None,
+            long_press: LongPressDetector::new(),
+            script_input: None,
+            recorder: None,
+            replayer: Some(replayer),
+            frame_count: 0,
+            frame_events: Vec::new(),
+            touch_viewport: [0, 0, DISPLAY_WIDTH, DISPLAY_HEIGHT],
+        })
+    }
+
+    pub fn set_key_map(&mut self, key_map: KeyMap) {
+        self.chord_detector = ChordDetector::new(key_map.chords.clone(), key_map.chord_window);
+        self.key_map = key_map;
+    }
+
+    /// Selects the per-page overrides of the `KeyMap`
+    pub fn set_page(&mut self, page_id: &str) {
+        if self.page_id != page_id {
+            self.page_id = page_id.to_string();
+        }
+    }
+
+    /// Held keys repeat after `repeat_delay`, only for the keyboard device
+    pub fn set_key_repeat(&mut self, repeat_delay: Option<Duration>) {
+        if let Some(key_manager) = &mut self.key_manager {
+            key_manager.set_repeat_delay(repeat_delay);
+        }
+    }
+
+    pub fn set_touch_manager(&mut self, touch_manager: TouchManager) {
+        self.touch_manager = Some(touch_manager);
+    }
+
+    pub fn set_script_input(&mut self, script_input: ScriptInput) {
+        self.script_input = Some(script_input);
+    }
+
+    pub fn record_to_file(&mut self, file_path: &str) -> io::Result<()> {
+        self.recorder = Some(InputRecorder::new(file_path)?);
+        info!("Recording input to {}", file_path);
+        Ok(())
+    }
+
+    /// Called once at the start of every frame
+    pub fn poll(&mut self) {
+        self.frame_count += 1;
+        self.frame_events.clear();
+
+        if let Some(replayer) = &mut self.replayer {
+            self.frame_events = replayer.events_for_frame(self.frame_count);
+        } else if let Some(key_manager) = &mut self.key_manager {
+            let key_map = &self.key_map;
+            let page_id = &self.page_id;
+            let to_actions = |physical_keys: Vec<String>| -> Vec<String> {
+                physical_keys.iter()
+                    .filter_map(|physical_key| key_map.get_action(page_id, physical_key))
+                    .map(|action| action.to_string())
+                    .collect()
+            };
+            let pressed = to_actions(key_manager.check_keys());
+            let held = to_actions(key_manager.held_keys());
+            self.frame_events = self.chord_detector.process(pressed, &held, Instant::now())
+                .into_iter()
+                .map(InputEvent::Key)
+                .collect();
+        }
+        if self.replayer.is_none() {
+            if let Some(touch_manager) = &mut self.touch_manager {
+                let mut touch_events = touch_manager.check_touches();
+                let long_press = self.long_press.process(&mut touch_events, Instant::now());
+                self.frame_events.extend(touch_events.into_iter().map(InputEvent::Touch));
+                if long_press {
+                    self.frame_events.push(InputEvent::Key(String::from("Help")));
+                }
+            }
+            if let Some(script_input) = &mut self.script_input {
+                self.frame_events.extend(script_input.next_event());
+            }
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(self.frame_count, &self.frame_events) {
+                warn!("Input recording stopped: {}", e);
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Returns the keys pressed in the current frame, each key is only returned once
+    pub fn check_keys(&mut self) -> Vec<String> {
+        let mut output = Vec::new();
+        self.frame_events.retain(|event| match event {
+            InputEvent::Key(label) => {
+                output.push(label.clone());
+                false
+            },
+            _ => true,
+        });
+        output
+    }
+
+    /// Removes the logical action from the current frame, returns true when it was pressed
+    pub fn take_key(&mut self, action: &str) -> bool {
+        let event_count = self.frame_events.len();
+        self.frame_events.retain(|event| !matches!(event, InputEvent::Key(label) if label == action));
+        self.frame_events.len() != event_count
+    }
+
+    /// Physical keys bound to the logical action on the current page
+    pub fn physical_keys(&self, action: &str) -> Vec<String> {
+        self.key_map.physical_keys(&self.page_id, action)
+    }
+
+    /// Touches are reported relative to the top left corner of `rect`, touches outside are dropped
+    pub fn set_touch_viewport(&mut self, rect: [usize; 4]) {
+        self.touch_viewport = rect;
+    }
+
+    pub fn reset_touch_viewport(&mut self) {
+        self.touch_viewport = [0, 0, DISPLAY_WIDTH, DISPLAY_HEIGHT];
+    }
+
+    /// Returns the touch events of the current frame, each event is only returned once
+    pub fn check_touches(&mut self) -> Vec<TouchEvent> {
+        let mut output = Vec::new();
+        let [x0, y0, x1, y1] = self.touch_viewport;
+        self.frame_events.retain(|event| match event {
+            InputEvent::Touch(touch_event) => {
+                let [x, y] = touch_event.position();
+                if x >= x0 && x < x1 && y >= y0 && y < y1 {
+                    output.push(touch_event.with_position(x - x0, y - y0));
+                }
+                false
+            },
+            _ => true,
+        });
+        output
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replayer.is_some()
+    }
+
+    /// True when a finite input source (replay file, script on stdin) has been used up
+    pub fn is_input_finished(&self) -> bool {
+        match (&self.replayer, &self.script_input) {
+            (Some(replayer), _) => replayer.is_finished(),
+            (None, Some(script_input)) => script_input.is_finished(),
+            (None, None) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_events_round_trip() {
+        let event = InputEvent::Key(String::from("Menu"));
+        assert_eq!(event.to_record(), "key Menu");
+        assert_eq!(InputEvent::from_record(&event.to_record()), Some(event));
+    }
+
+    #[test]
+    fn touch_events_round_trip() {
+        let events = [
+            TouchEvent::Down { x: 0, y: 5 },
+            TouchEvent::Move { x: 10, y: 20 },
+            TouchEvent::Up { x: 479, y: 479 },
+            TouchEvent::Tap { x: 3, y: 4 },
+        ];
+        for touch_event in events {
+            let event = InputEvent::Touch(touch_event);
+            assert_eq!(InputEvent::from_record(&event.to_record()), Some(event));
+        }
+    }
+
+    #[test]
+    fn invalid_records_are_rejected() {
+        assert_eq!(InputEvent::from_record(""), None);
+        assert_eq!(InputEvent::from_record("key"), None);
+        assert_eq!(InputEvent::from_record("touch press 1 2"), None);
+        assert_eq!(InputEvent::from_record("touch tap 1"), None);
+        assert_eq!(InputEvent::from_record("touch tap x 2"), None);
+        assert_eq!(InputEvent::from_record("scroll 1 2"), None);
+    }
+}
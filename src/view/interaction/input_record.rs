@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use crate::view::interaction::input_manager::InputEvent;
+
+/// # Input Recorder
+/// Writes every input event with its frame number, one event per line:
+/// `<frame> key <label>` or `<frame> touch <down/move/up/tap> <x> <y>`
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+}
+
+impl InputRecorder {
+    pub fn new(file_path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(file_path)?;
+        Ok(InputRecorder {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn record(&mut self, frame: u64, events: &[InputEvent]) -> io::Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        for event in events {
+            writeln!(self.writer, "{} {}", frame, event.to_record())?;
+        }
+        // flush per frame so a crash still leaves a usable bug report
+        self.writer.flush()
+    }
+}
+
+/// # Input Replayer
+/// Reads a file written by `InputRecorder` and hands the events back frame by frame.
+pub struct InputReplayer {
+    events: VecDeque<(u64, InputEvent)>,
+}
+
+impl InputReplayer {
+    pub fn new(file_path: &str) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(file_path)?);
+        let mut events = Vec::new();
+        for (line_index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (frame, event) = line.split_once(' ')
+                .and_then(|(frame, event)| Some((frame.parse::<u64>().ok()?, InputEvent::from_record(event)?)))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+                                              format!("{}:{}: invalid input record '{}'", file_path, line_index + 1, line)))?;
+            events.push((frame, event));
+        }
+        // keep the order of events inside one frame
+        events.sort_by_key(|(frame, _)| *frame);
+
+        Ok(InputReplayer {
+            events: events.into(),
+        })
+    }
+
+    pub fn events_for_frame(&mut self, frame: u64) -> Vec<InputEvent> {
+        let mut output = Vec::new();
+        while let Some((event_frame, _)) = self.events.front() {
+            if *event_frame > frame {
+                break;
+            }
+            output.push(self.events.pop_front().unwrap().1);
+        }
+        output
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,54 @@
+/// # Key Binding
+/// Logical action a page or widget reacts to, with the command it runs and the text the help overlay shows.
+/// Key input is dispatched through these tables, so the help lists exactly the bindings that are handled.
+#[derive(Clone, Debug)]
+pub struct KeyBinding<C> {
+    pub action: &'static str,
+    pub description: &'static str,
+    pub command: C,
+}
+
+impl<C: Clone> KeyBinding<C> {
+    pub fn new(action: &'static str, description: &'static str, command: C) -> Self {
+        KeyBinding { action, description, command }
+    }
+}
+
+/// Command bound to the logical action, `None` when the table does not handle it
+pub fn find_command<C: Clone>(bindings: &[KeyBinding<C>], action: &str) -> Option<C> {
+    bindings.iter().find(|binding| binding.action == action).map(|binding| binding.command.clone())
+}
+
+/// Commands shared by the page binding tables, `Page` carries the page specific ones
+#[derive(Clone, Debug)]
+pub enum PageCommand<A> {
+    Vertical(isize),
+    Horizontal(isize),
+    OpenMenu,
+    SwitchTo(&'static str),
+    Push(&'static str),
+    Pop,
+    Page(A),
+}
+
+/// Bindings listed by the help overlay under one heading
+pub struct HelpSection {
+    pub title: String,
+    /// Logical action and what it does
+    pub bindings: Vec<(String, String)>,
+}
+
+impl HelpSection {
+    pub fn new(title: &str, bindings: Vec<(String, String)>) -> Self {
+        HelpSection {
+            title: title.to_string(),
+            bindings,
+        }
+    }
+
+    pub fn from_bindings<C>(title: &str, bindings: &[KeyBinding<C>]) -> Self {
+        HelpSection::new(title, bindings.iter()
+            .map(|binding| (binding.action.to_string(), binding.description.to_string()))
+            .collect())
+    }
+}
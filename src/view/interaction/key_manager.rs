@@ -3,9 +3,15 @@ use std::time::{Duration, Instant};
 use device_query::{DeviceQuery, DeviceState, Keycode};
 use std::collections::HashMap;
 
+/// Time between repeats of a held key
+const KEY_REPEAT_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct KeyManager {
     device_state: DeviceState,
     key_timers: HashMap<Keycode, Instant>, // Track when each key was first pressed
+    key_last_output: HashMap<Keycode, Instant>,
+    /// Hold time before a key repeats, `None` disables repeating
+    repeat_delay: Option<Duration>,
 }
 
 impl KeyManager {
@@ -13,9 +19,15 @@ impl KeyManager {
         KeyManager {
             device_state: DeviceState::new(),
             key_timers: HashMap::new(),
+            key_last_output: HashMap::new(),
+            repeat_delay: None,
         }
     }
 
+    pub fn set_repeat_delay(&mut self, repeat_delay: Option<Duration>) {
+        self.repeat_delay = repeat_delay;
+    }
+
     pub fn check_keys(&mut self) -> Vec<String> {
         let now = Instant::now();
         let keys = self.device_state.get_keys();
@@ -26,28 +38,33 @@ impl KeyManager {
             // Check if the key is newly pressed
             if !self.key_timers.contains_key(key) {
                 self.key_timers.insert(*key, now);
+                self.key_last_output.insert(*key, now);
                 output.push(self.get_key_label(key));  // Output key label on first press
+            } else if let Some(repeat_delay) = self.repeat_delay {
+                // held keys repeat after the delay
+                let held_since = self.key_timers[key];
+                let last_output = self.key_last_output.get(key).copied().unwrap_or(held_since);
+                if now.duration_since(held_since) >= repeat_delay && now.duration_since(last_output) >= KEY_REPEAT_INTERVAL {
+                    self.key_last_output.insert(*key, now);
+                    output.push(self.get_key_label(key));
+                }
             }
         }
 
         // Remove keys that are no longer pressed
         self.key_timers.retain(|&k, &mut v| keys.contains(&k));
+        self.key_last_output.retain(|k, _| keys.contains(k));
         
         output
     }
 
+    /// Physical names of all keys currently held down
+    pub fn held_keys(&self) -> Vec<String> {
+        self.key_timers.keys().map(|key| self.get_key_label(key)).collect()
+    }
+
+    /// Physical key name, translated to a logical action by the `KeyMap`
     fn get_key_label(&self, key: &Keycode) -> String {
-        match key {
-            Keycode::Up => "Up".to_string(),
-            Keycode::Down => "Down".to_string(),
-            Keycode::Left => "Left".to_string(),
-            Keycode::Right => "Right".to_string(),
-            Keycode::M => "M".to_string(),
-            Keycode::Key1 => "1".to_string(),
-            Keycode::Key2 => "2".to_string(),
-            Keycode::Key3 => "3".to_string(),
-            Keycode::Key4 => "4".to_string(),
-            _ => "".to_string(),  // Ignore other keys
-        }
+        format!("{:?}", key)
     }
-}
+}
\ No newline at end of file
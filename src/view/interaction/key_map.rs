@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+/// # Key Map
+/// Maps physical key names (as reported by `KeyManager`) to logical actions.
+///
+/// Logical actions: `Up`, `Down`, `Left`, `Right`, `Menu`, `Back`, `Page1`..`Page4`,
+/// `Copy`, `Paste`, `Insert`, `MarkIn`, `MarkOut`, `ZoomIn`, `ZoomOut`, `ScaleUp`, `ScaleDown`, `Play`, `Help`, `Overview`, `Cursor`.
+///
+/// File format, `[page_id]` sections override the global bindings for that page only
+/// and `None` unbinds a key:
+/// ```text
+/// # physical = logical
+/// M = Menu
+/// Key1 = Page1
+///
+/// [wave_editor]
+/// C = Copy
+///
+/// # logical actions pressed together, window in milliseconds
+/// [chords]
+/// window = 150
+/// Menu+Left = MarkIn
+/// ```
+pub struct KeyMap {
+    bindings: HashMap<String, String>,
+    page_bindings: HashMap<String, HashMap<String, String>>,
+    pub chords: Vec<(Vec<String>, String)>,
+    pub chord_window: Duration,
+}
+
+impl KeyMap {
+    pub fn new() -> Self {
+        let bindings = [
+            ("Up", "Up"),
+            ("Down", "Down"),
+            ("Left", "Left"),
+            ("Right", "Right"),
+            ("M", "Menu"),
+            ("Escape", "Back"),
+            ("Key1", "Page1"),
+            ("Key2", "Page2"),
+            ("Key3", "Page3"),
+            ("Key4", "Page4"),
+            ("C", "Copy"),
+            ("V", "Paste"),
+            ("I", "Insert"),
+            ("Q", "MarkIn"),
+            ("W", "MarkOut"),
+            ("Z", "ZoomIn"),
+            ("X", "ZoomOut"),
+            ("A", "ScaleUp"),
+            ("S", "ScaleDown"),
+            ("Space", "Play"),
+            ("H", "Help"),
+            ("O", "Overview"),
+            ("K", "Cursor"),
+        ].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+        let chords = [
+            ("Menu+Left", "MarkIn"),
+            ("Menu+Right", "MarkOut"),
+            ("Menu+Up", "Copy"),
+            ("Menu+Down", "Paste"),
+        ].iter().map(|(k, v)| (Self::parse_chord(k), v.to_string())).collect();
+
+        KeyMap {
+            bindings,
+            page_bindings: HashMap::new(),
+            chords,
+            chord_window: Duration::from_millis(150),
+        }
+    }
+
+    /// Loads a keymap file on top of the default bindings
+    pub fn from_file(file_path: &str) -> io::Result<Self> {
+        let content = fs::read_to_string(file_path)?;
+        let mut key_map = KeyMap::new();
+        key_map.parse(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}", file_path, e)))?;
+        Ok(key_map)
+    }
+
+    fn parse(&mut self, content: &str) -> Result<(), String> {
+        let mut section: Option<String> = None;
+        for (line_index, line) in content.lines().enumerate() {
+            // only whole lines are comments, '#' may be part of a value
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = Some(line[1..line.len() - 1].trim().to_string());
+                continue;
+            }
+            let (physical, logical) = line.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .filter(|(k, v)| !k.is_empty() && !v.is_empty())
+                .ok_or_else(|| format!("{}: expected 'physical = logical', got '{}'", line_index + 1, line))?;
+            match section.as_deref() {
+                Some("chords") if physical == "window" => {
+                    let window = logical.parse::<u64>()
+                        .map_err(|_| format!("{}: invalid chord window '{}'", line_index + 1, logical))?;
+                    self.chord_window = Duration::from_millis(window);
+                },
+                Some("chords") => {
+                    let keys = Self::parse_chord(&physical);
+                    self.chords.retain(|(chord_keys, _)| *chord_keys != keys);
+                    if logical != "None" {
+                        self.chords.push((keys, logical));
+                    }
+                },
+                Some(page_id) => {
+                    self.page_bindings.entry(page_id.to_string()).or_default().insert(physical, logical);
+                },
+                None => {
+                    self.bindings.insert(physical, logical);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_chord(chord: &str) -> Vec<String> {
+        chord.split('+').map(|key| key.trim().to_string()).collect()
+    }
+
+    /// Resolves a physical key for the given page, page overrides win over global bindings
+    pub fn get_action(&self, page_id: &str, physical_key: &str) -> Option<&str> {
+        let action = self.page_bindings.get(page_id)
+            .and_then(|bindings| bindings.get(physical_key))
+            .or_else(|| self.bindings.get(physical_key))?;
+        if action == "None" {
+            None
+        } else {
+            Some(action.as_str())
+        }
+    }
+
+    /// Physical keys and chords that resolve to `action` on the given page, for the help overlay
+    pub fn physical_keys(&self, page_id: &str, action: &str) -> Vec<String> {
+        let page_keys = self.page_bindings.get(page_id).into_iter().flat_map(|bindings| bindings.keys());
+        let mut keys: Vec<String> = self.bindings.keys().chain(page_keys)
+            .filter(|physical_key| self.get_action(page_id, physical_key) == Some(action))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys.extend(self.chords.iter()
+            .filter(|(_, chord_action)| chord_action == action)
+            .map(|(chord_keys, _)| chord_keys.join("+")));
+        keys
+    }
+}
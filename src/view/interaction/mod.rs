@@ -1 +1,8 @@
-pub mod key_manager;
\ No newline at end of file
+pub mod key_manager;
+pub mod key_map;
+pub mod key_binding;
+pub mod chord_detector;
+pub mod input_manager;
+pub mod input_record;
+pub mod touch_manager;
+pub mod script_input;
\ No newline at end of file
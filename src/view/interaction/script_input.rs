@@ -0,0 +1,95 @@
+use std::io::{self, BufRead, BufReader};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use log::{info, warn};
+use crate::view::interaction::input_manager::InputEvent;
+
+/// # Script Input
+/// Reads input commands line by line from stdin or a local Unix socket.
+/// A line is either a logical action (`Up`, `Menu`, ...) or an input record
+/// without frame number (`key Up`, `touch tap 100 200`), empty lines and `#` comments are skipped.
+pub struct ScriptInput {
+    receiver: Receiver<InputEvent>,
+    is_finished: bool,
+}
+
+impl ScriptInput {
+    pub fn from_stdin() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            Self::read_lines(io::stdin().lock(), &sender);
+            info!("Script input from stdin finished");
+        });
+
+        info!("Script input from stdin");
+        ScriptInput {
+            receiver,
+            is_finished: false,
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn from_unix_socket(socket_path: &str) -> io::Result<Self> {
+        use std::os::unix::net::UnixListener;
+
+        // a socket file left over from a previous run would make bind fail
+        let _ = std::fs::remove_file(socket_path);
+        let listener = UnixListener::bind(socket_path)?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let sender = sender.clone();
+                        thread::spawn(move || Self::read_lines(BufReader::new(stream), &sender));
+                    },
+                    Err(e) => warn!("Script socket connection failed: {}", e),
+                }
+            }
+        });
+
+        info!("Script input from socket {}", socket_path);
+        Ok(ScriptInput {
+            receiver,
+            is_finished: false,
+        })
+    }
+
+    fn read_lines<R: BufRead>(reader: R, sender: &Sender<InputEvent>) {
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("Script input read failed: {}", e);
+                    break;
+                },
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let event = InputEvent::from_record(line).unwrap_or_else(|| InputEvent::Key(line.to_string()));
+            if sender.send(event).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Returns the next received event, one per frame so every page update sees each scripted key.
+    /// The remaining events stay queued in the channel.
+    pub fn next_event(&mut self) -> Option<InputEvent> {
+        match self.receiver.try_recv() {
+            Ok(event) => Some(event),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.is_finished = true;
+                None
+            },
+        }
+    }
+
+    /// True once the source is closed (stdin reached EOF) and all events were handed out
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+}
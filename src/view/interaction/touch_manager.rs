@@ -0,0 +1,235 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::mem;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+
+// evdev event types and codes, see linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0x00;
+const BTN_TOUCH: u16 = 0x14a;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_MT_SLOT: u16 = 0x2f;
+const ABS_MT_POSITION_X: u16 = 0x35;
+const ABS_MT_POSITION_Y: u16 = 0x36;
+const ABS_MT_TRACKING_ID: u16 = 0x39;
+
+/// Finger movement below this distance (in pixels) between down and up counts as a tap
+const TAP_DISTANCE: usize = 10;
+/// Touch held this long without moving is a long press
+const LONG_PRESS_TIME: Duration = Duration::from_millis(1000);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TouchEvent {
+    Down { x: usize, y: usize },
+    Move { x: usize, y: usize },
+    Up { x: usize, y: usize },
+    Tap { x: usize, y: usize },
+}
+
+impl TouchEvent {
+    pub fn position(&self) -> [usize; 2] {
+        match *self {
+            TouchEvent::Down { x, y } | TouchEvent::Move { x, y } |
+            TouchEvent::Up { x, y } | TouchEvent::Tap { x, y } => [x, y],
+        }
+    }
+
+    /// Same event at another position
+    pub fn with_position(&self, x: usize, y: usize) -> TouchEvent {
+        match *self {
+            TouchEvent::Down { .. } => TouchEvent::Down { x, y },
+            TouchEvent::Move { .. } => TouchEvent::Move { x, y },
+            TouchEvent::Up { .. } => TouchEvent::Up { x, y },
+            TouchEvent::Tap { .. } => TouchEvent::Tap { x, y },
+        }
+    }
+}
+
+/// # Touch Manager
+/// Reads a touch panel through the evdev multitouch protocol (type B, slot 0 only)
+/// and turns it into touch events in display coordinates.
+pub struct TouchManager {
+    receiver: Receiver<TouchEvent>,
+}
+
+impl TouchManager {
+    /// `touch_range` is the maximum raw x/y reported by the panel,
+    /// `display_size` the resolution the coordinates are scaled to
+    pub fn new(device_path: &str, touch_range: [i32; 2], display_size: [usize; 2]) -> io::Result<Self> {
+        let device = File::open(device_path)?;
+        let (sender, receiver) = mpsc::channel();
+        let thread_device_path = device_path.to_string();
+
+        thread::spawn(move || {
+            let mut parser = TouchParser::new(touch_range, display_size);
+            let mut device = device;
+            // struct input_event { struct timeval time; __u16 type; __u16 code; __s32 value; }
+            let time_size = mem::size_of::<usize>() * 2;
+            let mut buffer = vec![0u8; time_size + 8];
+            loop {
+                if let Err(e) = device.read_exact(&mut buffer) {
+                    warn!("Touch device {} closed: {}", thread_device_path, e);
+                    break;
+                }
+                let event_type = u16::from_ne_bytes([buffer[time_size], buffer[time_size + 1]]);
+                let code = u16::from_ne_bytes([buffer[time_size + 2], buffer[time_size + 3]]);
+                let value = i32::from_ne_bytes([buffer[time_size + 4], buffer[time_size + 5],
+                                                buffer[time_size + 6], buffer[time_size + 7]]);
+                for touch_event in parser.process(event_type, code, value) {
+                    if sender.send(touch_event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        info!("Touch input from {}", device_path);
+        Ok(TouchManager {
+            receiver,
+        })
+    }
+
+    /// Returns the touch events received since the last call
+    pub fn check_touches(&mut self) -> Vec<TouchEvent> {
+        let mut output = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(touch_event) => output.push(touch_event),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        output
+    }
+}
+
+/// # Long Press Detector
+/// Reports a touch that stays down in place for `LONG_PRESS_TIME`. The tap that ends a
+/// long press is dropped, so the page below does not see it as a tap.
+pub struct LongPressDetector {
+    down: Option<(Instant, [usize; 2])>,
+    fired: bool,
+}
+
+impl LongPressDetector {
+    pub fn new() -> Self {
+        LongPressDetector {
+            down: None,
+            fired: false,
+        }
+    }
+
+    /// Filters the touch events of one frame, returns true once per long press
+    pub fn process(&mut self, touch_events: &mut Vec<TouchEvent>, now: Instant) -> bool {
+        touch_events.retain(|touch_event| {
+            match *touch_event {
+                TouchEvent::Down { x, y } => {
+                    self.down = Some((now, [x, y]));
+                    self.fired = false;
+                },
+                TouchEvent::Move { x, y } => {
+                    if let Some((_, [down_x, down_y])) = self.down {
+                        if x.abs_diff(down_x) >= TAP_DISTANCE || y.abs_diff(down_y) >= TAP_DISTANCE {
+                            self.down = None;
+                        }
+                    }
+                },
+                TouchEvent::Up { .. } => self.down = None,
+                TouchEvent::Tap { .. } => return !self.fired,
+            }
+            true
+        });
+        match self.down {
+            Some((down_time, _)) if !self.fired && now.duration_since(down_time) >= LONG_PRESS_TIME => {
+                self.fired = true;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+struct TouchParser {
+    touch_range: [i32; 2],
+    display_size: [usize; 2],
+    slot: i32,
+    raw_position: [i32; 2],
+    is_touching: bool,
+    was_touching: bool,
+    down_position: [usize; 2],
+    last_position: [usize; 2],
+}
+
+impl TouchParser {
+    fn new(touch_range: [i32; 2], display_size: [usize; 2]) -> Self {
+        TouchParser {
+            touch_range,
+            display_size,
+            slot: 0,
+            raw_position: [0, 0],
+            is_touching: false,
+            was_touching: false,
+            down_position: [0, 0],
+            last_position: [0, 0],
+        }
+    }
+
+    fn process(&mut self, event_type: u16, code: u16, value: i32) -> Vec<TouchEvent> {
+        match (event_type, code) {
+            (EV_ABS, ABS_MT_SLOT) => self.slot = value,
+            // other fingers are ignored
+            (EV_ABS, _) if self.slot != 0 => {},
+            (EV_ABS, ABS_MT_TRACKING_ID) => self.is_touching = value >= 0,
+            (EV_ABS, ABS_MT_POSITION_X) | (EV_ABS, ABS_X) => self.raw_position[0] = value,
+            (EV_ABS, ABS_MT_POSITION_Y) | (EV_ABS, ABS_Y) => self.raw_position[1] = value,
+            (EV_KEY, BTN_TOUCH) => self.is_touching = value != 0,
+            (EV_SYN, SYN_REPORT) => return self.report(),
+            _ => {},
+        }
+        Vec::new()
+    }
+
+    fn report(&mut self) -> Vec<TouchEvent> {
+        let [x, y] = self.scale_position();
+        let mut output = Vec::new();
+        match (self.was_touching, self.is_touching) {
+            (false, true) => {
+                self.down_position = [x, y];
+                output.push(TouchEvent::Down { x, y });
+            },
+            (true, true) => {
+                if [x, y] != self.last_position {
+                    output.push(TouchEvent::Move { x, y });
+                }
+            },
+            (true, false) => {
+                // the lifted finger keeps its last known position
+                let [x, y] = self.last_position;
+                output.push(TouchEvent::Up { x, y });
+                if x.abs_diff(self.down_position[0]) < TAP_DISTANCE && y.abs_diff(self.down_position[1]) < TAP_DISTANCE {
+                    output.push(TouchEvent::Tap { x, y });
+                }
+            },
+            (false, false) => {},
+        }
+        if self.is_touching {
+            self.last_position = [x, y];
+        }
+        self.was_touching = self.is_touching;
+        output
+    }
+
+    fn scale_position(&self) -> [usize; 2] {
+        let mut position = [0usize; 2];
+        for i in 0..2 {
+            let raw = self.raw_position[i].clamp(0, self.touch_range[i].max(1));
+            position[i] = (raw as usize * (self.display_size[i] - 1)) / self.touch_range[i].max(1) as usize;
+        }
+        position
+    }
+}
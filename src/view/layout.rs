@@ -0,0 +1,259 @@
+/// # Layout
+/// Declarative description of how an area is split between named blocks. Rows place their children
+/// side by side, columns stack them and grids put them into equal cells filled row by row.
+/// Along a row or column, children with a fixed `size` are placed first and the rest of the space
+/// is shared by the other children in proportion to their `weight`.
+pub struct Layout {
+    kind: LayoutKind,
+    name: Option<String>,
+    size: Option<usize>,
+    weight: usize,
+    padding: usize,
+    spacing: usize,
+}
+
+enum LayoutKind {
+    Leaf,
+    Row(Vec<Layout>),
+    Column(Vec<Layout>),
+    Grid { columns: usize, children: Vec<Layout> },
+}
+
+/// Rectangles assigned by `Layout::compute`, as `[x0, y0, x1, y1]` with inclusive corners
+/// like `block_rect` and `draw_rectangle`
+pub struct LayoutRects {
+    rects: Vec<(String, [usize; 4])>,
+}
+
+impl LayoutRects {
+    /// None for unknown names and for areas that got no space, e.g. behind oversized fixed siblings
+    pub fn get(&self, name: &str) -> Option<[usize; 4]> {
+        self.rects.iter().find(|(rect_name, _)| rect_name == name).map(|(_, rect)| *rect)
+    }
+}
+
+impl Layout {
+    fn new(kind: LayoutKind) -> Self {
+        Layout {
+            kind,
+            name: None,
+            size: None,
+            weight: 1,
+            padding: 0,
+            spacing: 0,
+        }
+    }
+
+    /// Area of one block
+    pub fn slot(name: &str) -> Self {
+        Layout::new(LayoutKind::Leaf).name(name)
+    }
+
+    /// Empty space
+    pub fn space() -> Self {
+        Layout::new(LayoutKind::Leaf)
+    }
+
+    pub fn row(children: Vec<Layout>) -> Self {
+        Layout::new(LayoutKind::Row(children))
+    }
+
+    pub fn column(children: Vec<Layout>) -> Self {
+        Layout::new(LayoutKind::Column(children))
+    }
+
+    pub fn grid(columns: usize, children: Vec<Layout>) -> Self {
+        Layout::new(LayoutKind::Grid { columns: columns.max(1), children })
+    }
+
+    /// Records the area of a row, column or grid as well
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Fixed size along the parent row or column
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn weight(mut self, weight: usize) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Space kept free on all four sides
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Space between the children
+    pub fn spacing(mut self, spacing: usize) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// `area` is `[x0, y0, x1, y1]` with exclusive end, e.g. `[0, 0, width, height]`
+    pub fn compute(&self, area: [usize; 4]) -> LayoutRects {
+        let mut rects = LayoutRects { rects: Vec::new() };
+        self.place(area, &mut rects);
+        rects
+    }
+
+    fn place(&self, area: [usize; 4], rects: &mut LayoutRects) {
+        let [x0, y0, x1, y1] = area;
+        let x0 = (x0 + self.padding).min(x1);
+        let y0 = (y0 + self.padding).min(y1);
+        let x1 = x1.saturating_sub(self.padding).max(x0);
+        let y1 = y1.saturating_sub(self.padding).max(y0);
+
+        // an empty area has no inclusive rect, it would reach outside the parent
+        if let Some(name) = self.name.as_ref().filter(|_| x1 > x0 && y1 > y0) {
+            rects.rects.push((name.clone(), [x0, y0, x1 - 1, y1 - 1]));
+        }
+        match &self.kind {
+            LayoutKind::Leaf => {},
+            LayoutKind::Row(children) => {
+                for (child, (start, end)) in children.iter().zip(self.split(children, x0, x1)) {
+                    child.place([start, y0, end, y1], rects);
+                }
+            },
+            LayoutKind::Column(children) => {
+                for (child, (start, end)) in children.iter().zip(self.split(children, y0, y1)) {
+                    child.place([x0, start, x1, end], rects);
+                }
+            },
+            LayoutKind::Grid { columns, children } => {
+                let rows = (children.len() + columns - 1) / columns;
+                let cell_width = (x1 - x0).saturating_sub(self.spacing * (columns - 1)) / columns;
+                let cell_height = (y1 - y0).saturating_sub(self.spacing * rows.saturating_sub(1)) / rows.max(1);
+                for (index, child) in children.iter().enumerate() {
+                    let cell_x = x0 + (index % columns) * (cell_width + self.spacing);
+                    let cell_y = y0 + (index / columns) * (cell_height + self.spacing);
+                    child.place([cell_x, cell_y, cell_x + cell_width, cell_y + cell_height], rects);
+                }
+            },
+        }
+    }
+
+    /// Start and end of each child between `start` and `end`, fixed sizes first, then by weight
+    fn split(&self, children: &[Layout], start: usize, end: usize) -> Vec<(usize, usize)> {
+        let available = (end - start).saturating_sub(self.spacing * children.len().saturating_sub(1));
+        let fixed: usize = children.iter().filter_map(|child| child.size).sum();
+        let flexible = available.saturating_sub(fixed);
+        let total_weight: usize = children.iter().filter(|child| child.size.is_none()).map(|child| child.weight).sum();
+        let last_flexible = children.iter().rposition(|child| child.size.is_none());
+
+        let mut spans = Vec::with_capacity(children.len());
+        let mut position = start;
+        let mut flexible_left = flexible;
+        for (index, child) in children.iter().enumerate() {
+            let length = match child.size {
+                Some(size) => size,
+                // the last flexible child takes the rounding remainder
+                None if Some(index) == last_flexible => flexible_left,
+                None => {
+                    let length = flexible * child.weight / total_weight.max(1);
+                    flexible_left -= length;
+                    length
+                },
+            };
+            let child_end = (position + length).min(end);
+            spans.push((position, child_end));
+            position = (child_end + self.spacing).min(end);
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_share_the_flexible_space() {
+        let rects = Layout::row(vec![
+            Layout::slot("a"),
+            Layout::slot("b").weight(3),
+        ]).compute([0, 0, 400, 100]);
+        assert_eq!(rects.get("a"), Some([0, 0, 99, 99]));
+        assert_eq!(rects.get("b"), Some([100, 0, 399, 99]));
+    }
+
+    #[test]
+    fn fixed_sizes_are_placed_first() {
+        let rects = Layout::column(vec![
+            Layout::space().size(20),
+            Layout::slot("a"),
+            Layout::slot("b").size(30),
+        ]).compute([0, 0, 100, 200]);
+        assert_eq!(rects.get("a"), Some([0, 20, 99, 169]));
+        assert_eq!(rects.get("b"), Some([0, 170, 99, 199]));
+    }
+
+    #[test]
+    fn spacing_and_padding_are_kept_free() {
+        let rects = Layout::row(vec![
+            Layout::slot("a"),
+            Layout::slot("b"),
+        ]).padding(5).spacing(10).name("row").compute([0, 0, 120, 20]);
+        assert_eq!(rects.get("row"), Some([5, 5, 114, 14]));
+        assert_eq!(rects.get("a"), Some([5, 5, 54, 14]));
+        assert_eq!(rects.get("b"), Some([65, 5, 114, 14]));
+    }
+
+    #[test]
+    fn last_flexible_child_takes_the_remainder() {
+        let rects = Layout::row(vec![
+            Layout::slot("a"),
+            Layout::slot("b"),
+            Layout::slot("c"),
+        ]).compute([0, 0, 100, 10]);
+        assert_eq!(rects.get("a"), Some([0, 0, 32, 9]));
+        assert_eq!(rects.get("b"), Some([33, 0, 65, 9]));
+        assert_eq!(rects.get("c"), Some([66, 0, 99, 9]));
+    }
+
+    #[test]
+    fn grid_fills_cells_row_by_row() {
+        let rects = Layout::grid(2, vec![
+            Layout::slot("a"),
+            Layout::slot("b"),
+            Layout::slot("c"),
+        ]).spacing(10).compute([0, 0, 210, 110]);
+        assert_eq!(rects.get("a"), Some([0, 0, 99, 49]));
+        assert_eq!(rects.get("b"), Some([110, 0, 209, 49]));
+        assert_eq!(rects.get("c"), Some([0, 60, 99, 109]));
+    }
+
+    #[test]
+    fn fixed_sizes_larger_than_the_area_are_cut_and_empty_slots_dropped() {
+        let rects = Layout::row(vec![
+            Layout::slot("a").size(80),
+            Layout::slot("b").size(80),
+            Layout::slot("c"),
+        ]).compute([0, 0, 100, 10]);
+        assert_eq!(rects.get("a"), Some([0, 0, 79, 9]));
+        assert_eq!(rects.get("b"), Some([80, 0, 99, 9]));
+        assert_eq!(rects.get("c"), None);
+    }
+
+    #[test]
+    fn zero_size_area_does_not_panic() {
+        let rects = Layout::column(vec![
+            Layout::slot("a").size(20),
+            Layout::grid(3, vec![Layout::slot("b")]).spacing(4),
+        ]).padding(10).spacing(5).compute([0, 0, 0, 0]);
+        assert_eq!(rects.get("a"), None);
+        assert_eq!(rects.get("b"), None);
+    }
+
+    #[test]
+    fn unnamed_areas_are_not_recorded() {
+        let rects = Layout::row(vec![Layout::space(), Layout::slot("a")]).compute([0, 0, 10, 10]);
+        assert_eq!(rects.get(""), None);
+        assert_eq!(rects.get("a"), Some([5, 0, 9, 9]));
+    }
+}
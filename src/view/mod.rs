@@ -1,3 +1,7 @@
 pub mod display;
 pub mod interaction;
+pub mod widget;
+pub mod page;
+pub mod layout;
+pub mod frame_timing;
 pub mod view_main;
\ No newline at end of file
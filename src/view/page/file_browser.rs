@@ -0,0 +1,557 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{debug, warn};
+use crate::model::core::{Core, CoreCommand};
+use crate::view::display::display::Display;
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::{HelpSection, KeyBinding, PageCommand};
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::layout::Layout;
+use crate::view::view_main::{PageInterface, PageNavigator, rect_contains};
+use crate::view::widget::text_input::TextInputResult;
+use crate::view::widget::notifier::Notifier;
+use crate::settings::Settings;
+
+/// Track files are raw little endian `i32` samples
+pub const SUPPORTED_EXTENSIONS: [&str; 3] = ["trk", "bin", "raw"];
+/// Extension of the files written by the browser
+const SAVE_EXTENSION: &str = "trk";
+/// Mount points below these directories are listed as USB drives
+const USB_MOUNT_ROOTS: [&str; 3] = ["/media/", "/mnt/", "/run/media/"];
+
+const TITLE_HEIGHT: usize = 16;
+const TITLE_WIDTH: usize = 100;
+const MESSAGE_HEIGHT: usize = 16;
+const ROW_HEIGHT: usize = 24;
+const NAME_MAX_CHARS: usize = 40;
+const FILE_NAME_MAX_LENGTH: usize = 32;
+const OVERWRITE_DIALOG_ID: &str = "file_browser_overwrite";
+const FILE_NAME_INPUT_ID: &str = "file_browser_file_name";
+
+/// What the chosen path is used for, set by the page that opens the browser
+#[derive(Clone, Debug)]
+pub enum FileBrowserRequest {
+    Load { track_index: usize },
+    Save { track_index: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum EntryKind {
+    Location,
+    Directory,
+    File,
+    SaveHere,
+}
+
+struct BrowserEntry {
+    name: String,
+    path: PathBuf,
+    kind: EntryKind,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// # Page: File Browser
+/// Lists the data directory and mounted USB drives, `Right` opens a directory or picks a file,
+/// `Left` goes up and `Back` cancels. The picked file is loaded into or saved from the
+/// track of the `FileBrowserRequest`.
+pub struct PageFileBrowser {
+    display_ref: Rc<RefCell<Display>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    notifier: Rc<RefCell<Notifier>>,
+    request_ref: Rc<RefCell<Option<FileBrowserRequest>>>,
+    request: Option<FileBrowserRequest>,
+    settings: Rc<RefCell<Settings>>,
+    data_directory: PathBuf,
+    current_dir: Option<PathBuf>,
+    entries: Vec<BrowserEntry>,
+    selected_entry: usize,
+    scroll: usize,
+    message: Option<(String, (u8, u8, u8))>,
+    /// Directory the file name input saves into while it is open
+    save_directory: Option<PathBuf>,
+    overwrite_path: Option<PathBuf>,
+    title_rect: [usize; 4],
+    location_rect: [usize; 4],
+    list_rect: [usize; 4],
+    message_rect: [usize; 4],
+}
+
+impl PageFileBrowser {
+    pub fn new(display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>,
+               core: Rc<RefCell<Core>>, notifier: Rc<RefCell<Notifier>>, request_ref: Rc<RefCell<Option<FileBrowserRequest>>>, settings: Rc<RefCell<Settings>>) -> Self {
+        let data_directory = PathBuf::from(settings.borrow().get_text("data_directory"));
+        PageFileBrowser {
+            display_ref,
+            input_manager,
+            navigator,
+            core,
+            notifier,
+            request_ref,
+            request: None,
+            settings,
+            data_directory,
+            current_dir: None,
+            entries: Vec::new(),
+            selected_entry: 0,
+            scroll: 0,
+            message: None,
+            save_directory: None,
+            overwrite_path: None,
+            title_rect: [0, 0, 0, 0],
+            location_rect: [0, 0, 0, 0],
+            list_rect: [0, 0, 0, 0],
+            message_rect: [0, 0, 0, 0],
+        }
+    }
+
+    /// Title and location on top, the entries in the middle and the message line at the bottom
+    fn layout() -> Layout {
+        Layout::column(vec![
+            Layout::row(vec![
+                Layout::slot("title").size(TITLE_WIDTH),
+                Layout::slot("location"),
+            ]).size(TITLE_HEIGHT).spacing(10),
+            Layout::slot("list"),
+            Layout::slot("message").size(MESSAGE_HEIGHT),
+        ]).padding(10).spacing(10)
+    }
+
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let rects = Self::layout().compute([0, 0, page_size[0], page_size[1]]);
+        self.title_rect = rects.get("title").unwrap_or([0, 0, 0, 0]);
+        self.location_rect = rects.get("location").unwrap_or([0, 0, 0, 0]);
+        self.list_rect = rects.get("list").unwrap_or([0, 0, 0, 0]);
+        self.message_rect = rects.get("message").unwrap_or([0, 0, 0, 0]);
+    }
+
+    /// Rows that fit into the list area
+    fn visible_rows(&self) -> usize {
+        ((self.list_rect[3] + 1 - self.list_rect[1] + 4) / ROW_HEIGHT).max(1)
+    }
+
+    fn row_rect(&self, row: usize) -> [usize; 4] {
+        let [x0, y0, x1, _] = self.list_rect;
+        let y = y0 + row * ROW_HEIGHT;
+        [x0, y, x1, y + ROW_HEIGHT - 4]
+    }
+
+    /// The data directory followed by the mounted USB drives
+    fn locations(&self) -> Vec<(String, PathBuf)> {
+        let mut locations = vec![(String::from("Data"), self.data_directory.clone())];
+        for mount_point in usb_mount_points() {
+            let name = mount_point.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            locations.push((format!("USB {}", name), mount_point));
+        }
+        locations
+    }
+
+    fn read_entries(&mut self) {
+        self.entries.clear();
+        self.selected_entry = 0;
+        self.scroll = 0;
+
+        let dir = match &self.current_dir {
+            Some(dir) => dir.clone(),
+            None => {
+                for (name, path) in self.locations() {
+                    self.entries.push(BrowserEntry { name, path, kind: EntryKind::Location, size: 0, modified: None });
+                }
+                return;
+            },
+        };
+
+        if dir == self.data_directory {
+            if let Err(e) = fs::create_dir_all(&dir) {
+                self.show_error(format!("{}: {}", dir.display(), e));
+                return;
+            }
+        }
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                self.show_error(format!("{}: {}", dir.display(), e));
+                return;
+            },
+        };
+
+        let mut entries = Vec::new();
+        for entry in read_dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    debug!("Skipping {}: {}", name, e);
+                    continue;
+                },
+            };
+            let kind = if metadata.is_dir() {
+                EntryKind::Directory
+            } else if is_supported_file(&entry.path()) {
+                EntryKind::File
+            } else {
+                continue;
+            };
+            entries.push(BrowserEntry { name, path: entry.path(), kind, size: metadata.len(), modified: metadata.modified().ok() });
+        }
+        // directories first, then files, both by name
+        entries.sort_by(|a, b| (a.kind != EntryKind::Directory, a.name.to_lowercase()).cmp(&(b.kind != EntryKind::Directory, b.name.to_lowercase())));
+
+        if let Some(FileBrowserRequest::Save { .. }) = self.request {
+            self.entries.push(BrowserEntry { name: String::from("[Save Here]"), path: dir.clone(), kind: EntryKind::SaveHere, size: 0, modified: None });
+        }
+        self.entries.extend(entries);
+    }
+
+    fn show_error(&mut self, message: String) {
+        warn!("File browser: {}", message);
+        self.message = Some((message, (255, 0, 0)));
+    }
+
+    fn is_location_root(&self, dir: &Path) -> bool {
+        self.locations().iter().any(|(_, path)| path == dir)
+    }
+
+    fn open_parent(&mut self) {
+        let parent = match &self.current_dir {
+            Some(dir) if self.is_location_root(dir) => None,
+            Some(dir) => dir.parent().map(|p| p.to_path_buf()),
+            None => return,
+        };
+        self.current_dir = parent;
+        self.message = None;
+        self.read_entries();
+        self.page_view_draw();
+    }
+
+    fn open_selected(&mut self) {
+        let (kind, path) = match self.entries.get(self.selected_entry) {
+            Some(entry) => (entry.kind.clone(), entry.path.clone()),
+            None => return,
+        };
+        match kind {
+            EntryKind::Location | EntryKind::Directory => {
+                self.current_dir = Some(path);
+                self.message = None;
+                self.read_entries();
+            },
+            EntryKind::File => self.choose_file(path),
+            EntryKind::SaveHere => {
+                let track_index = match self.request {
+                    Some(FileBrowserRequest::Save { track_index }) => track_index,
+                    _ => return,
+                };
+                let track_name = self.core.borrow().wave_container.get_track_name(track_index).replace(' ', "_").to_lowercase();
+                let file_name = format!("{}_{}", track_name, format_file_timestamp(SystemTime::now()));
+                self.notifier.borrow_mut().open_text_input(FILE_NAME_INPUT_ID, "File Name", &file_name, FILE_NAME_MAX_LENGTH);
+                self.save_directory = Some(path);
+            },
+        }
+        self.page_view_draw();
+    }
+
+    /// Saves into the chosen directory once the file name input was confirmed
+    fn process_file_name_input(&mut self) {
+        let result = match self.notifier.borrow_mut().take_text_input_result(FILE_NAME_INPUT_ID) {
+            Some(result) => result,
+            None => return,
+        };
+        let dir = match self.save_directory.take() {
+            Some(dir) => dir,
+            None => return,
+        };
+        if let TextInputResult::Confirmed(file_name) = result {
+            let file_name = file_name.trim();
+            if file_name.is_empty() {
+                self.notifier.borrow_mut().warning("File name is empty");
+            } else if is_supported_file(Path::new(file_name)) {
+                self.choose_file(dir.join(file_name));
+            } else {
+                self.choose_file(dir.join(format!("{}.{}", file_name, SAVE_EXTENSION)));
+            }
+        }
+    }
+
+    /// Saving over an existing file has to be confirmed first
+    fn choose_file(&mut self, path: PathBuf) {
+        if let (Some(FileBrowserRequest::Save { .. }), true) = (&self.request, path.exists()) {
+            let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            self.notifier.borrow_mut().confirm(OVERWRITE_DIALOG_ID, "Overwrite File",
+                                               &format!("{} already exists. Replace it with the track data?", file_name), "Overwrite");
+            self.overwrite_path = Some(path);
+        } else {
+            self.finish_request(path);
+        }
+    }
+
+    /// Hands the path to the core, leaves the page on success and shows the error otherwise
+    fn finish_request(&mut self, path: PathBuf) {
+        let file_path = path.to_string_lossy().to_string();
+        let command = match self.request {
+            Some(FileBrowserRequest::Load { track_index }) => CoreCommand::ReadTrackFromFile { track_index, file_path: file_path.clone() },
+            Some(FileBrowserRequest::Save { track_index }) => CoreCommand::SaveTrackToFile { track_index, file_path: file_path.clone() },
+            None => {
+                self.notifier.borrow_mut().error("No track to load or save");
+                return;
+            },
+        };
+        let result = self.core.borrow_mut().execute(command);
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(file_path);
+        match (result, &self.request) {
+            (Ok(()), Some(FileBrowserRequest::Save { .. })) => {
+                self.notifier.borrow_mut().info(&format!("Saved {}", file_name));
+                self.navigator.borrow_mut().pop();
+            },
+            (Ok(()), _) => {
+                self.notifier.borrow_mut().info(&format!("Loaded {}", file_name));
+                self.navigator.borrow_mut().pop();
+            },
+            (Err(e), _) => self.notifier.borrow_mut().error(&e),
+        }
+    }
+
+    /// Keys of the page, dispatched by `process_key_input` and listed by the help overlay
+    fn key_bindings() -> Vec<KeyBinding<PageCommand<()>>> {
+        vec![
+            KeyBinding::new("Up", "Previous entry", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next entry", PageCommand::Vertical(1)),
+            KeyBinding::new("Left", "Parent directory", PageCommand::Horizontal(-1)),
+            KeyBinding::new("Right", "Open directory / pick file", PageCommand::Horizontal(1)),
+            KeyBinding::new("Menu", "Cancel", PageCommand::Pop),
+            KeyBinding::new("Back", "Cancel", PageCommand::Pop),
+        ]
+    }
+
+    fn page_view_draw(&mut self) {
+        let mut display = self.display_ref.borrow_mut();
+        display.clean();
+
+        let title = match self.request {
+            Some(FileBrowserRequest::Load { track_index }) => format!("Load {}", self.core.borrow().wave_container.get_track_name(track_index)),
+            Some(FileBrowserRequest::Save { track_index }) => format!("Save {}", self.core.borrow().wave_container.get_track_name(track_index)),
+            None => String::from("Files"),
+        };
+        // the location fills the rest of the line, cut at the front so the current directory stays readable
+        let [location_x0, location_y0, location_x1, _] = self.location_rect;
+        let location = match &self.current_dir {
+            Some(dir) => shorten_front(&display, &dir.to_string_lossy(), location_x1 - location_x0),
+            None => String::from("Locations"),
+        };
+        display.text(&title, 1, self.title_rect[0], self.title_rect[1], 1, 1, (255, 255, 255));
+        display.text(&location, 1, location_x0, location_y0, 1, 1, (200, 200, 200));
+
+        let [list_x0, list_y0, _, _] = self.list_rect;
+        if self.entries.is_empty() && self.message.is_none() {
+            display.text("No supported files", 1, list_x0 + 10, list_y0 + 4, 1, 1, (200, 200, 200));
+        }
+        for (row, entry) in self.entries.iter().enumerate().skip(self.scroll).take(self.visible_rows()) {
+            let [x0, y, x1, y1] = self.row_rect(row - self.scroll);
+            let color = if row == self.selected_entry { (100,30,30) } else { (30,30,30) };
+            display.draw_rectangle(x0, y, x1, y1, color, true);
+            let (name, name_color) = match entry.kind {
+                EntryKind::Location | EntryKind::Directory => (format!("{}/", shorten_end(&entry.name, NAME_MAX_CHARS)), (255, 200, 0)),
+                EntryKind::SaveHere => (entry.name.clone(), (0, 255, 0)),
+                EntryKind::File => (shorten_end(&entry.name, NAME_MAX_CHARS), (255, 255, 255)),
+            };
+            display.text(&name, 1, x0 + 10, y + 4, 1, 1, name_color);
+            if entry.kind == EntryKind::File {
+                display.text(&format_size(entry.size), 1, x0 + 250, y + 4, 1, 1, (200, 200, 200));
+                if let Some(modified) = entry.modified {
+                    display.text(&format_date(modified), 1, x0 + 330, y + 4, 1, 1, (200, 200, 200));
+                }
+            }
+        }
+
+        if let Some((message, color)) = &self.message {
+            let [x0, y0, x1, _] = self.message_rect;
+            display.text(&shorten_end(message, (x1 - x0) / 6), 1, x0, y0, 1, 1, *color);
+        }
+    }
+}
+
+impl PageInterface for PageFileBrowser {
+    fn page_view_init(&mut self) {}
+
+    fn page_enter(&mut self) {
+        self.apply_layout();
+        self.request = self.request_ref.borrow_mut().take();
+        self.save_directory = None;
+        self.overwrite_path = None;
+        // the data directory may have been changed on the settings page
+        let data_directory = PathBuf::from(self.settings.borrow().get_text("data_directory"));
+        if data_directory != self.data_directory {
+            self.data_directory = data_directory;
+            self.current_dir = None;
+        }
+        if self.current_dir.is_none() {
+            self.current_dir = Some(self.data_directory.clone());
+        }
+        self.message = None;
+        self.read_entries();
+        self.page_view_draw();
+    }
+
+    fn process_key_input(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            debug!("{:?}", first_key);
+            self.dispatch_key(&Self::key_bindings(), first_key);
+        }
+    }
+
+    fn process_key_input_block_menu(&mut self) {}
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                // the first tap selects a row, a tap on the selected row opens it
+                for row in 0..self.visible_rows().min(self.entries.len().saturating_sub(self.scroll)) {
+                    if rect_contains(self.row_rect(row), x, y) {
+                        if self.selected_entry == self.scroll + row {
+                            self.open_selected();
+                        } else {
+                            self.selected_entry = self.scroll + row;
+                            self.page_view_draw();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn navigate_vertical(&mut self, dir: isize) {
+        let new_index = self.selected_entry as isize + dir;
+        if new_index >= 0 && new_index < self.entries.len() as isize {
+            self.selected_entry = new_index as usize;
+            if self.selected_entry < self.scroll {
+                self.scroll = self.selected_entry;
+            } else if self.selected_entry >= self.scroll + self.visible_rows() {
+                self.scroll = self.selected_entry + 1 - self.visible_rows();
+            }
+            self.page_view_draw();
+        }
+    }
+
+    fn navigate_horizontal(&mut self, dir: isize) {
+        if dir < 0 {
+            self.open_parent();
+        } else {
+            self.open_selected();
+        }
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
+    }
+
+    fn call_block_menu(&mut self) {}
+
+    fn call_page(&mut self) {
+        self.page_view_draw();
+    }
+
+    fn help(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Page", &Self::key_bindings())]
+    }
+
+    fn page_view_update(&mut self) {
+        let overwrite = self.notifier.borrow_mut().take_dialog_result(OVERWRITE_DIALOG_ID);
+        if let Some(confirmed) = overwrite {
+            match self.overwrite_path.take() {
+                Some(path) if confirmed => self.finish_request(path),
+                _ => {},
+            }
+        }
+        self.process_file_name_input();
+        self.process_key_input();
+        self.process_touch_input();
+    }
+}
+
+fn is_supported_file(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .map_or(false, |extension| SUPPORTED_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Mounted removable drives, read from `/proc/mounts`
+fn usb_mount_points() -> Vec<PathBuf> {
+    let mounts = match fs::read_to_string("/proc/mounts") {
+        Ok(mounts) => mounts,
+        Err(_) => return Vec::new(),
+    };
+    mounts.lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        // spaces in mount points are escaped as \040
+        .map(|mount_point| mount_point.replace("\\040", " "))
+        .filter(|mount_point| USB_MOUNT_ROOTS.iter().any(|root| mount_point.starts_with(root)))
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn format_size(size: u64) -> String {
+    match size {
+        0..=1023 => format!("{} B", size),
+        1024..=1048575 => format!("{:.1} KB", size as f64 / 1024.0),
+        _ => format!("{:.1} MB", size as f64 / 1048576.0),
+    }
+}
+
+/// Date and time in UTC, split into `(year, month, day, hour, minute, second)`
+fn date_time(time: SystemTime) -> (i64, u32, u32, u64, u64, u64) {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let days = (seconds / 86400) as i64;
+    let seconds_of_day = seconds % 86400;
+    // days since 1970-01-01 to a civil date, see Howard Hinnant's `civil_from_days`
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day, seconds_of_day / 3600, seconds_of_day % 3600 / 60, seconds_of_day % 60)
+}
+
+fn format_date(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, _) = date_time(time);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
+
+fn format_file_timestamp(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = date_time(time);
+    format!("{:04}{:02}{:02}_{:02}{:02}{:02}", year, month, day, hour, minute, second)
+}
+
+/// Keeps the start of the text, marks the cut with `..`
+pub(crate) fn shorten_end(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return String::from(text);
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(2)).collect();
+    format!("{}..", kept)
+}
+
+/// Keeps the end of the text within `max_width` pixels of the small font, useful for long paths
+fn shorten_front(display: &Display, text: &str, max_width: usize) -> String {
+    if display.text_width(text, 1, 1, 1) <= max_width {
+        return String::from(text);
+    }
+    let count = text.chars().count();
+    (1..=count)
+        .map(|skipped| format!("..{}", text.chars().skip(skipped).collect::<String>()))
+        .find(|shortened| display.text_width(shortened, 1, 1, 1) <= max_width)
+        .unwrap_or_default()
+}
@@ -0,0 +1,243 @@
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::rc::Rc;
+use log::debug;
+use crate::model::core::{Core, CoreCommand};
+use crate::model::track_loader::{GeneratorParameter, TrackLoader, TrackWaveGenerator, WaveGenerateType};
+use crate::view::display::display::Display;
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::{HelpSection, KeyBinding, PageCommand};
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::layout::Layout;
+use crate::view::view_main::{PageInterface, PageNavigator, wave_generate_type_name};
+use crate::view::widget::notifier::Notifier;
+use crate::view::widget::parameter_list::ParameterList;
+
+const PARAMETER_NAMES: [&str; 6] = ["Type", "Amplitude", "Wavelength", "Phase", "Duty", "Y Shift"];
+const TITLE_HEIGHT: usize = 30;
+/// Phase change of one key press, 15 degrees
+const PHASE_STEP: f32 = PI / 12.0;
+
+/// # Page: Wave Generator Editor
+/// Edits the parameters of the wave generator on the selected track,
+/// `Up`/`Down` select a parameter and `Left`/`Right` change it.
+pub struct PageGeneratorEditor {
+    display_ref: Rc<RefCell<Display>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    notifier: Rc<RefCell<Notifier>>,
+    track_index: usize,
+    generator: Option<TrackWaveGenerator>,
+    parameters: ParameterList,
+    parameter_rect: [usize; 4],
+    preview_rect: [usize; 4],
+}
+
+impl PageGeneratorEditor {
+    pub fn new(display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>, core: Rc<RefCell<Core>>, notifier: Rc<RefCell<Notifier>>) -> Self {
+        PageGeneratorEditor {
+            display_ref,
+            input_manager,
+            navigator,
+            core,
+            notifier,
+            track_index: 0,
+            generator: None,
+            parameters: ParameterList::new(&PARAMETER_NAMES).single_line(),
+            parameter_rect: [0, 0, 0, 0],
+            preview_rect: [0, 0, 0, 0],
+        }
+    }
+
+    /// Parameter rows on top, the preview of one period below
+    fn layout(&self) -> Layout {
+        Layout::column(vec![
+            Layout::space().size(TITLE_HEIGHT),
+            Layout::slot("parameters").size(self.parameters.height()),
+            Layout::slot("preview"),
+        ]).padding(10).spacing(10)
+    }
+
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let rects = self.layout().compute([0, 0, page_size[0], page_size[1]]);
+        self.parameter_rect = rects.get("parameters").unwrap_or([0, 0, 0, 0]);
+        self.parameters.set_rect(self.parameter_rect);
+        self.preview_rect = rects.get("preview").unwrap_or([0, 0, 0, 0]);
+    }
+
+    /// Copies the generator of the selected track, `None` if the track has no generator
+    fn read_generator(&mut self) {
+        let core = self.core.borrow();
+        self.track_index = core.wave_container.get_selected_track();
+        self.generator = match &core.data_loader_container.track_loader[self.track_index] {
+            TrackLoader::WaveGenerator(generator) => Some(generator.clone()),
+            _ => None,
+        };
+    }
+
+    fn parameter_value(generator: &TrackWaveGenerator, index: usize) -> String {
+        match index {
+            0 => String::from(wave_generate_type_name(generator.get_wave_type())),
+            1 => generator.get_amplitude().to_string(),
+            2 => generator.get_wavelength().to_string(),
+            3 => format!("{:.3} rad", generator.get_phase()),
+            4 => format!("{:.2}", generator.get_duty()),
+            _ => generator.get_y_shift().to_string(),
+        }
+    }
+
+    /// Step of one key press for each parameter
+    fn step_parameter(generator: &TrackWaveGenerator, index: usize, dir: i32) -> GeneratorParameter {
+        match index {
+            0 => {
+                let wave_types = [WaveGenerateType::Sine, WaveGenerateType::Triangle, WaveGenerateType::Sawtooth,
+                                  WaveGenerateType::Rectangular(generator.get_duty()), WaveGenerateType::Noise];
+                let current = wave_types.iter()
+                    .position(|t| wave_generate_type_name(t) == wave_generate_type_name(generator.get_wave_type()))
+                    .unwrap_or(0) as i32;
+                let next = (current + dir).rem_euclid(wave_types.len() as i32) as usize;
+                GeneratorParameter::WaveType(wave_types[next].clone())
+            },
+            1 => GeneratorParameter::Amplitude((generator.get_amplitude() + dir).max(0)),
+            2 => {
+                // larger wavelengths change in bigger steps
+                let wavelength = generator.get_wavelength() as i32;
+                let step = (wavelength / 10).max(1);
+                GeneratorParameter::Wavelength((wavelength + dir * step).max(1) as usize)
+            },
+            3 => GeneratorParameter::Phase((generator.get_phase() + dir as f32 * PHASE_STEP).rem_euclid(2.0 * PI)),
+            4 => GeneratorParameter::Duty((generator.get_duty() + dir as f32 * 0.05).clamp(0.0, 1.0)),
+            _ => GeneratorParameter::YShift(generator.get_y_shift() + dir),
+        }
+    }
+
+    fn change_parameter(&mut self, dir: i32) {
+        let generator = match &self.generator {
+            Some(generator) => generator,
+            None => return,
+        };
+        let parameter = Self::step_parameter(generator, self.parameters.selected(), dir);
+        debug!("Generator parameter: {:?}", parameter);
+        let command = CoreCommand::SetGeneratorParameter { track_index: self.track_index, parameter };
+        let result = self.core.borrow_mut().execute(command);
+        if let Err(e) = result {
+            self.notifier.borrow_mut().error(&e);
+        }
+        self.read_generator();
+        self.page_view_draw();
+    }
+
+    /// Keys of the page, dispatched by `process_key_input` and listed by the help overlay
+    fn key_bindings() -> Vec<KeyBinding<PageCommand<()>>> {
+        vec![
+            KeyBinding::new("Up", "Previous parameter", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next parameter", PageCommand::Vertical(1)),
+            KeyBinding::new("Left", "Decrease value", PageCommand::Horizontal(-1)),
+            KeyBinding::new("Right", "Increase value", PageCommand::Horizontal(1)),
+            KeyBinding::new("Menu", "Close editor", PageCommand::Pop),
+            KeyBinding::new("Back", "Close editor", PageCommand::Pop),
+        ]
+    }
+
+    fn page_view_draw(&mut self) {
+        let mut display = self.display_ref.borrow_mut();
+        display.clean();
+        display.text(&format!("Generator Track {}", self.track_index + 1), 1, 10, 10, 1, 1, (255, 255, 255));
+
+        let generator = match &self.generator {
+            Some(generator) => generator,
+            None => {
+                display.text("No wave generator on this track", 1, self.parameter_rect[0], self.parameter_rect[1], 1, 1, (255, 0, 0));
+                return;
+            },
+        };
+
+        self.parameters.draw(&mut display, |index| format!("< {} >", Self::parameter_value(generator, index)));
+
+        // one period of the regenerated wave
+        let [x0, y0, x1, y1] = self.preview_rect;
+        display.draw_rectangle(x0, y0, x1, y1, (30, 30, 30), false);
+        let wave = &generator.wave_date_buffer;
+        if wave.is_empty() || x1 == x0 {
+            return;
+        }
+        let max_abs = wave.iter().map(|v| v.saturating_abs()).max().unwrap_or(1).max(1) as f32;
+        let mid_y = (y0 + y1) as f32 / 2.0;
+        let half_height = (y1 - y0) as f32 / 2.0 - 4.0;
+        display.draw_line(x0, mid_y as usize, x1, mid_y as usize, (60, 60, 60));
+        let width = x1 - x0;
+        let mut previous: Option<(usize, usize)> = None;
+        for px in 0..=width {
+            let value = wave[px * (wave.len() - 1) / width] as f32;
+            let y = (mid_y - value / max_abs * half_height) as usize;
+            if let Some((previous_x, previous_y)) = previous {
+                display.draw_line(previous_x, previous_y, x0 + px, y, (0, 200, 255));
+            }
+            previous = Some((x0 + px, y));
+        }
+    }
+}
+
+impl PageInterface for PageGeneratorEditor {
+    fn page_view_init(&mut self) {}
+
+    fn page_enter(&mut self) {
+        self.apply_layout();
+        self.read_generator();
+        self.page_view_draw();
+    }
+
+    fn process_key_input(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            debug!("{:?}", first_key);
+            self.dispatch_key(&Self::key_bindings(), first_key);
+        }
+    }
+
+    fn process_key_input_block_menu(&mut self) {}
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                match self.parameters.touch_input(x, y) {
+                    Some(0) => self.page_view_draw(),
+                    Some(dir) => self.change_parameter(dir as i32),
+                    None => {},
+                }
+            }
+        }
+    }
+
+    fn navigate_vertical(&mut self, dir: isize) {
+        if self.parameters.navigate(dir) {
+            self.page_view_draw();
+        }
+    }
+
+    fn navigate_horizontal(&mut self, dir: isize) {
+        self.change_parameter(dir as i32);
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
+    }
+
+    fn call_block_menu(&mut self) {}
+
+    fn call_page(&mut self) {
+        self.page_view_draw();
+    }
+
+    fn help(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Page", &Self::key_bindings())]
+    }
+
+    fn page_view_update(&mut self) {
+        self.process_key_input();
+        self.process_touch_input();
+    }
+}
@@ -0,0 +1,7 @@
+pub mod generator_editor;
+pub mod file_browser;
+pub mod operator_rack;
+pub mod settings;
+pub mod track_overview;
+pub mod xy_scope;
+pub mod spectrum;
@@ -0,0 +1,469 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use log::debug;
+use crate::model::core::{Core, CoreCommand};
+use crate::model::operator_rack::{OperatorRack, INPUT_NODE_ID, OPERATOR_TYPES, OUTPUT_NODE_ID};
+use crate::view::display::display::Display;
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::{find_command, HelpSection, KeyBinding, PageCommand};
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::layout::Layout;
+use crate::view::view_main::{PageInterface, PageNavigator, MENU_RECT};
+use crate::view::widget::menu::{menu_key_help, Menu, MenuItem, MenuResult};
+use crate::view::widget::notifier::Notifier;
+
+const NODE_WIDTH: usize = 100;
+const NODE_HEADER_HEIGHT: usize = 20;
+const PORT_HEIGHT: usize = 18;
+const NODES_TOP: usize = 40;
+/// Space below the nodes for the cable hint
+const HINT_HEIGHT: usize = 30;
+/// Operators that fit between the input and output nodes
+const MAX_OPERATORS: usize = 12;
+/// Taps closer than this to a port hit the port
+const PORT_TOUCH_DISTANCE: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PortRef {
+    node_id: usize,
+    port_id: usize,
+    is_input: bool,
+}
+
+/// Screen position and current port values of one rack node
+#[derive(Clone, Debug, PartialEq)]
+struct NodeView {
+    node_id: usize,
+    name: String,
+    rect: [usize; 4],
+    input_values: Vec<i32>,
+    output_values: Vec<i32>,
+}
+
+impl NodeView {
+    fn port_position(&self, port_id: usize, is_input: bool) -> [usize; 2] {
+        let x = if is_input { self.rect[0] } else { self.rect[2] };
+        [x, self.rect[1] + NODE_HEADER_HEIGHT + port_id * PORT_HEIGHT + PORT_HEIGHT / 2]
+    }
+}
+
+#[derive(Clone, Debug)]
+enum RackMenuAction {
+    AddOperator(String),
+    Cable,
+    CancelCable,
+    DeleteCables,
+    RemoveOperator,
+}
+
+/// # Page: Operator Rack
+/// Operators as boxes with their ports and live values. Arrow keys select a port,
+/// `Insert` (or the menu) starts a cable and finishes it on a port of the other direction.
+pub struct PageOperatorRack {
+    display_ref: Rc<RefCell<Display>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    notifier: Rc<RefCell<Notifier>>,
+    nodes: Vec<NodeView>,
+    cables: Vec<(PortRef, PortRef)>,
+    selected_port: usize,
+    cable_start: Option<PortRef>,
+    menu: Option<Menu<RackMenuAction>>,
+    redraw: bool,
+}
+
+impl PageOperatorRack {
+    pub fn new(display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>, core: Rc<RefCell<Core>>, notifier: Rc<RefCell<Notifier>>) -> Self {
+        PageOperatorRack {
+            display_ref,
+            input_manager,
+            navigator,
+            core,
+            notifier,
+            nodes: Vec::new(),
+            cables: Vec::new(),
+            selected_port: 0,
+            cable_start: None,
+            menu: None,
+            redraw: true,
+        }
+    }
+
+    /// Input node on the left, output node on the right, operators in two columns between them
+    fn layout() -> Layout {
+        Layout::row(vec![
+            Layout::slot("input").size(NODE_WIDTH),
+            Layout::grid(2, (0..MAX_OPERATORS).map(|i| Layout::slot(&format!("operator_{}", i))).collect()).spacing(20),
+            Layout::slot("output").size(NODE_WIDTH),
+        ]).padding(10).spacing(20)
+    }
+
+    fn node_views(rack: &OperatorRack, page_size: [usize; 2]) -> Vec<NodeView> {
+        let rects = Self::layout().compute([0, NODES_TOP - 10, page_size[0], page_size[1].saturating_sub(HINT_HEIGHT)]);
+        let mut node_ids: Vec<usize> = rack.operators.keys().copied()
+            .filter(|id| *id != INPUT_NODE_ID && *id != OUTPUT_NODE_ID)
+            .collect();
+        node_ids.sort();
+        node_ids.insert(0, INPUT_NODE_ID);
+        node_ids.push(OUTPUT_NODE_ID);
+
+        let mut nodes = Vec::new();
+        for (index, node_id) in node_ids.iter().enumerate() {
+            let node = match rack.operators.get(node_id) {
+                Some(node) => node,
+                None => continue,
+            };
+            let input_values: Vec<i32> = (0..node.input_port_number())
+                .map(|port_id| node.get_input_port(&port_id).map_or(0, |port| port.borrow().value))
+                .collect();
+            let output_values: Vec<i32> = (0..node.output_port_number())
+                .map(|port_id| node.get_output_port(&port_id).map_or(0, |port| port.borrow().value))
+                .collect();
+            let slot = match *node_id {
+                INPUT_NODE_ID => String::from("input"),
+                OUTPUT_NODE_ID => String::from("output"),
+                _ => format!("operator_{}", index - 1),
+            };
+            // the node height follows its ports, only the top left corner comes from the layout
+            let [x, y, _, _] = rects.get(&slot).unwrap_or([0, 0, 0, 0]);
+            let port_rows = input_values.len().max(output_values.len());
+            nodes.push(NodeView {
+                node_id: *node_id,
+                name: String::from(node.get_name()),
+                rect: [x, y, x + NODE_WIDTH, y + NODE_HEADER_HEIGHT + port_rows * PORT_HEIGHT + 4],
+                input_values,
+                output_values,
+            });
+        }
+        nodes
+    }
+
+    /// All ports in selection order, node by node, inputs before outputs
+    fn ports(&self) -> Vec<PortRef> {
+        let mut ports = Vec::new();
+        for node in self.nodes.iter() {
+            for port_id in 0..node.input_values.len() {
+                ports.push(PortRef { node_id: node.node_id, port_id, is_input: true });
+            }
+            for port_id in 0..node.output_values.len() {
+                ports.push(PortRef { node_id: node.node_id, port_id, is_input: false });
+            }
+        }
+        ports
+    }
+
+    fn node(&self, node_id: usize) -> Option<&NodeView> {
+        self.nodes.iter().find(|node| node.node_id == node_id)
+    }
+
+    fn selected(&self) -> Option<PortRef> {
+        self.ports().get(self.selected_port).copied()
+    }
+
+    /// Reads the rack from the core, returns true when anything visible changed
+    fn read_rack(&mut self) -> bool {
+        let (nodes, cables) = {
+            let core = self.core.borrow();
+            let rack = &core.operator_rack;
+            let cables: Vec<(PortRef, PortRef)> = rack.connections.iter().map(|c| (
+                PortRef { node_id: c.src_node_id, port_id: c.src_port_id, is_input: false },
+                PortRef { node_id: c.dst_node_id, port_id: c.dst_port_id, is_input: true },
+            )).collect();
+            (Self::node_views(rack, self.display_ref.borrow().viewport_size()), cables)
+        };
+        let changed = nodes != self.nodes || cables != self.cables;
+        self.nodes = nodes;
+        self.cables = cables;
+        let port_number = self.ports().len();
+        if self.selected_port >= port_number {
+            self.selected_port = port_number.saturating_sub(1);
+        }
+        changed
+    }
+
+    fn execute_core_command(&mut self, command: CoreCommand) {
+        let result = self.core.borrow_mut().execute(command);
+        if let Err(e) = result {
+            self.notifier.borrow_mut().error(&e);
+        }
+        self.read_rack();
+        self.redraw = true;
+    }
+
+    /// Starts a cable at the selected port or finishes the pending one there
+    fn cable(&mut self) {
+        let port = match self.selected() {
+            Some(port) => port,
+            None => return,
+        };
+        match self.cable_start.take() {
+            None => {
+                self.cable_start = Some(port);
+            },
+            Some(start) if start.is_input == port.is_input => {
+                self.notifier.borrow_mut().warning("Cables go from an output port to an input port");
+            },
+            Some(start) => {
+                let (src, dst) = if start.is_input { (port, start) } else { (start, port) };
+                self.execute_core_command(CoreCommand::ConnectPorts {
+                    src_node_id: src.node_id,
+                    src_port_id: src.port_id,
+                    dst_node_id: dst.node_id,
+                    dst_port_id: dst.port_id,
+                });
+            },
+        }
+        self.redraw = true;
+    }
+
+    /// Keys of the page, dispatched by `process_key_input` and listed by the help overlay
+    fn key_bindings() -> Vec<KeyBinding<PageCommand<RackMenuAction>>> {
+        vec![
+            KeyBinding::new("Up", "Previous port", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next port", PageCommand::Vertical(1)),
+            KeyBinding::new("Left", "Previous node", PageCommand::Horizontal(-1)),
+            KeyBinding::new("Right", "Next node", PageCommand::Horizontal(1)),
+            KeyBinding::new("Insert", "Start / finish cable", PageCommand::Page(RackMenuAction::Cable)),
+            KeyBinding::new("Menu", "Rack menu", PageCommand::OpenMenu),
+            KeyBinding::new("Page1", "Data loader", PageCommand::SwitchTo("data_loader")),
+            KeyBinding::new("Page2", "Wave editor", PageCommand::SwitchTo("wave_editor")),
+            KeyBinding::new("Page4", "Settings", PageCommand::Push("settings")),
+            KeyBinding::new("Overview", "Track overview", PageCommand::SwitchTo("track_overview")),
+            KeyBinding::new("Back", "Cancel cable / back", PageCommand::Pop),
+        ]
+    }
+
+    fn handle_menu_result(&mut self, result: MenuResult<RackMenuAction>) {
+        match result {
+            MenuResult::Selected(action) => {
+                debug!("Menu Executed: {:?}", action);
+                self.menu = None;
+                match action {
+                    RackMenuAction::AddOperator(operator_type) => self.execute_core_command(CoreCommand::AddOperator { operator_type }),
+                    RackMenuAction::Cable => self.cable(),
+                    RackMenuAction::CancelCable => self.cable_start = None,
+                    RackMenuAction::DeleteCables => {
+                        if let Some(port) = self.selected() {
+                            self.execute_core_command(CoreCommand::DisconnectPort { node_id: port.node_id, port_id: port.port_id, is_input: port.is_input });
+                        }
+                    },
+                    RackMenuAction::RemoveOperator => {
+                        if let Some(port) = self.selected() {
+                            self.execute_core_command(CoreCommand::RemoveOperator(port.node_id));
+                        }
+                    },
+                }
+                self.redraw = true;
+            },
+            MenuResult::Closed => {
+                self.menu = None;
+                self.redraw = true;
+            },
+            MenuResult::None => {},
+        }
+    }
+
+    fn page_view_draw(&mut self) {
+        let selected = self.selected();
+        let mut display = self.display_ref.borrow_mut();
+        display.clean();
+        display.text("Operator Rack", 1, 10, 10, 1, 1, (255, 255, 255));
+
+        for node in self.nodes.iter() {
+            let [x0, y0, x1, y1] = node.rect;
+            display.draw_rectangle(x0, y0, x1, y1, (30, 30, 30), true);
+            display.draw_rectangle(x0, y0, x1, y0 + NODE_HEADER_HEIGHT - 2, (0, 60, 100), true);
+            display.text(&node.name, 1, x0 + 5, y0 + 2, 1, 1, (0, 255, 0));
+            for (port_id, value) in node.input_values.iter().enumerate() {
+                let [px, py] = node.port_position(port_id, true);
+                display.draw_rectangle(px, py - 3, px + 6, py + 3, (200, 200, 200), true);
+                display.text(&value.to_string(), 1, px + 10, py - 8, 1, 1, (200, 200, 200));
+            }
+            for (port_id, value) in node.output_values.iter().enumerate() {
+                let [px, py] = node.port_position(port_id, false);
+                display.draw_rectangle(px - 6, py - 3, px, py + 3, (200, 200, 200), true);
+                let value_text = value.to_string();
+                let value_width = display.text_width(&value_text, 1, 1, 1);
+                display.text(&value_text, 1, px - 10 - value_width, py - 8, 1, 1, (200, 200, 200));
+            }
+        }
+
+        for (src, dst) in self.cables.iter() {
+            if let (Some(src_node), Some(dst_node)) = (self.node(src.node_id), self.node(dst.node_id)) {
+                let [sx, sy] = src_node.port_position(src.port_id, false);
+                let [dx, dy] = dst_node.port_position(dst.port_id, true);
+                display.draw_line(sx, sy, dx, dy, (0, 200, 255));
+            }
+        }
+
+        // selected port and the pending cable
+        let port_position = |port: PortRef| self.node(port.node_id).map(|node| node.port_position(port.port_id, port.is_input));
+        if let Some([px, py]) = selected.and_then(port_position) {
+            display.draw_rectangle(px.saturating_sub(8), py - 8, px + 8, py + 8, (255, 0, 0), false);
+            if let Some([sx, sy]) = self.cable_start.and_then(port_position) {
+                display.draw_line(sx, sy, px, py, (255, 255, 0));
+            }
+        }
+
+        if self.cable_start.is_some() {
+            let hint_y = display.viewport_size()[1].saturating_sub(HINT_HEIGHT) + 5;
+            display.text("Select the other end of the cable", 1, 10, hint_y, 1, 1, (255, 255, 0));
+        }
+    }
+}
+
+impl PageInterface for PageOperatorRack {
+    fn page_view_init(&mut self) {}
+
+    fn page_enter(&mut self) {
+        self.menu = None;
+        self.read_rack();
+        self.page_view_draw();
+    }
+
+    fn process_key_input(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            debug!("{:?}", first_key);
+            // back cancels a started cable before it leaves the page
+            if self.cable_start.is_some() && matches!(find_command(&Self::key_bindings(), first_key), Some(PageCommand::Pop)) {
+                self.cable_start = None;
+                self.redraw = true;
+            } else if let Some(action) = self.dispatch_key(&Self::key_bindings(), first_key) {
+                self.handle_menu_result(MenuResult::Selected(action));
+            }
+        }
+    }
+
+    fn process_key_input_block_menu(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let (Some(first_key), Some(menu)) = (key.get(0), &mut self.menu) {
+            let result = menu.key_input(first_key);
+            self.handle_menu_result(result);
+        }
+    }
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                if let Some(menu) = &mut self.menu {
+                    let result = menu.touch_input(MENU_RECT, x, y);
+                    self.handle_menu_result(result);
+                    continue;
+                }
+                // a tap selects a port, a tap on the selected port starts or finishes a cable
+                let hit = self.ports().iter().position(|port| {
+                    self.node(port.node_id).map_or(false, |node| {
+                        let [px, py] = node.port_position(port.port_id, port.is_input);
+                        px.abs_diff(x) <= PORT_TOUCH_DISTANCE && py.abs_diff(y) <= PORT_TOUCH_DISTANCE
+                    })
+                });
+                if let Some(index) = hit {
+                    if index == self.selected_port {
+                        self.cable();
+                    } else {
+                        self.selected_port = index;
+                        self.redraw = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn navigate_vertical(&mut self, dir: isize) {
+        let new_index = self.selected_port as isize + dir;
+        if new_index >= 0 && new_index < self.ports().len() as isize {
+            self.selected_port = new_index as usize;
+            self.redraw = true;
+        }
+    }
+
+    /// Jumps to the first port of the previous or next node
+    fn navigate_horizontal(&mut self, dir: isize) {
+        let ports = self.ports();
+        let node_index = match ports.get(self.selected_port).and_then(|port| self.nodes.iter().position(|node| node.node_id == port.node_id)) {
+            Some(node_index) => node_index as isize,
+            None => return,
+        };
+        let new_node_index = node_index + dir;
+        if new_node_index < 0 || new_node_index >= self.nodes.len() as isize {
+            return;
+        }
+        let node_id = self.nodes[new_node_index as usize].node_id;
+        if let Some(index) = ports.iter().position(|port| port.node_id == node_id) {
+            self.selected_port = index;
+            self.redraw = true;
+        }
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
+    }
+
+    fn open_menu(&mut self) {
+        let selected = self.selected();
+        let operator_items = OPERATOR_TYPES.iter().map(|operator_type| {
+            let mut label = operator_type.to_string();
+            label[..1].make_ascii_uppercase();
+            MenuItem::new_action(&label, RackMenuAction::AddOperator(operator_type.to_string()))
+        }).collect();
+        let mut add_operator = MenuItem::new_submenu("Add Operator", Menu::new("Add Operator", operator_items));
+        if self.nodes.len() >= MAX_OPERATORS + 2 {
+            add_operator = add_operator.disabled();
+        }
+        let mut items = vec![add_operator];
+        if self.cable_start.is_some() {
+            items.push(MenuItem::new_action("Finish Cable Here", RackMenuAction::Cable));
+            items.push(MenuItem::new_action("Cancel Cable", RackMenuAction::CancelCable));
+        } else {
+            items.push(MenuItem::new_action("Start Cable Here", RackMenuAction::Cable));
+        }
+        items.push(MenuItem::new_action("Delete Cables At Port", RackMenuAction::DeleteCables));
+        let mut remove_operator = MenuItem::new_action("Remove Operator", RackMenuAction::RemoveOperator);
+        if selected.map_or(true, |port| port.node_id == INPUT_NODE_ID || port.node_id == OUTPUT_NODE_ID) {
+            remove_operator = remove_operator.disabled();
+        }
+        items.push(remove_operator);
+        self.menu = Some(Menu::new("Operator Rack", items));
+    }
+
+    fn call_block_menu(&mut self) {
+        self.open_menu();
+    }
+
+    fn call_page(&mut self) {
+        self.page_view_draw();
+    }
+
+    fn help(&self) -> Vec<HelpSection> {
+        match &self.menu {
+            Some(menu) => vec![menu_key_help(), menu.help_section("Menu: Operator Rack")],
+            None => vec![HelpSection::from_bindings("Page", &Self::key_bindings())],
+        }
+    }
+
+    fn page_view_update(&mut self) {
+        if self.menu.is_some() {
+            self.process_key_input_block_menu();
+            self.process_touch_input();
+            if let Some(menu) = &mut self.menu {
+                menu.draw(&mut self.display_ref.borrow_mut(), MENU_RECT);
+            }
+            return;
+        }
+
+        self.process_key_input();
+        self.process_touch_input();
+        if self.menu.is_some() {
+            self.page_view_draw();
+            return;
+        }
+        // live port values
+        if self.read_rack() || self.redraw {
+            self.redraw = false;
+            self.page_view_draw();
+        }
+    }
+}
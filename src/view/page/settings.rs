@@ -0,0 +1,195 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use log::debug;
+use crate::settings::{SettingKind, SettingValue, Settings};
+use crate::view::display::display::Display;
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::{HelpSection, KeyBinding, PageCommand};
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::layout::Layout;
+use crate::view::view_main::{PageInterface, PageNavigator};
+use crate::view::widget::notifier::Notifier;
+use crate::view::widget::parameter_list::ParameterList;
+use crate::view::widget::text_input::TextInputResult;
+
+const TITLE_HEIGHT: usize = 30;
+const TEXT_INPUT_ID: &str = "settings_text";
+
+/// # Page: Settings
+/// One row per setting of the settings schema, `Up`/`Down` select a setting and `Left`/`Right`
+/// change it, `Menu` toggles switches and edits text. Every change is saved right away.
+pub struct PageSettings {
+    display_ref: Rc<RefCell<Display>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    notifier: Rc<RefCell<Notifier>>,
+    settings: Rc<RefCell<Settings>>,
+    parameters: ParameterList,
+}
+
+impl PageSettings {
+    pub fn new(display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>,
+               notifier: Rc<RefCell<Notifier>>, settings: Rc<RefCell<Settings>>) -> Self {
+        let labels: Vec<&'static str> = settings.borrow().definitions().iter().map(|definition| definition.label).collect();
+        PageSettings {
+            display_ref,
+            input_manager,
+            navigator,
+            notifier,
+            settings,
+            parameters: ParameterList::new(&labels).single_line(),
+        }
+    }
+
+    /// Title line on top, the setting rows below
+    fn layout() -> Layout {
+        Layout::column(vec![
+            Layout::space().size(TITLE_HEIGHT),
+            Layout::slot("settings"),
+        ]).padding(10).spacing(10)
+    }
+
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let rects = Self::layout().compute([0, 0, page_size[0], page_size[1]]);
+        self.parameters.set_rect(rects.get("settings").unwrap_or([0, 0, 0, 0]));
+    }
+
+    fn set_value(&mut self, index: usize, value: SettingValue) {
+        let result = {
+            let mut settings = self.settings.borrow_mut();
+            debug!("Setting {} = {:?}", settings.definitions()[index].key, value);
+            settings.set_value(index, value)
+                .and_then(|_| settings.save().map_err(|e| format!("Unable to save settings: {}", e)))
+        };
+        if let Err(e) = result {
+            self.notifier.borrow_mut().error(&e);
+        }
+        self.page_view_draw();
+    }
+
+    fn change_setting(&mut self, dir: i32) {
+        let value = {
+            let settings = self.settings.borrow();
+            settings.definitions()[self.parameters.selected()].step(settings.value(self.parameters.selected()), dir)
+        };
+        self.set_value(self.parameters.selected(), value);
+    }
+
+    /// Switches toggle, text settings open the text input, other kinds step forward
+    fn activate_setting(&mut self) {
+        let (kind, value, label) = {
+            let settings = self.settings.borrow();
+            let definition = &settings.definitions()[self.parameters.selected()];
+            (definition.kind.clone(), settings.value(self.parameters.selected()).to_text(), definition.label)
+        };
+        match kind {
+            SettingKind::Text { max_length } => {
+                self.notifier.borrow_mut().open_text_input(TEXT_INPUT_ID, label, &value, max_length);
+            },
+            _ => self.change_setting(1),
+        }
+    }
+
+    /// Keys of the page, dispatched by `process_key_input` and listed by the help overlay
+    fn key_bindings() -> Vec<KeyBinding<PageCommand<()>>> {
+        vec![
+            KeyBinding::new("Up", "Previous setting", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next setting", PageCommand::Vertical(1)),
+            KeyBinding::new("Left", "Decrease / previous option", PageCommand::Horizontal(-1)),
+            KeyBinding::new("Right", "Increase / next option", PageCommand::Horizontal(1)),
+            KeyBinding::new("Menu", "Toggle switch / edit text", PageCommand::OpenMenu),
+            KeyBinding::new("Back", "Back", PageCommand::Pop),
+        ]
+    }
+
+    /// Stores the text once the text input of the selected setting was confirmed
+    fn process_text_input(&mut self) {
+        let result = self.notifier.borrow_mut().take_text_input_result(TEXT_INPUT_ID);
+        if let Some(TextInputResult::Confirmed(text)) = result {
+            self.set_value(self.parameters.selected(), SettingValue::Text(text.trim().to_string()));
+        }
+    }
+
+    fn page_view_draw(&mut self) {
+        let mut display = self.display_ref.borrow_mut();
+        display.clean();
+        display.text("Settings", 1, 10, 10, 1, 1, (255, 255, 255));
+
+        let settings = self.settings.borrow();
+        self.parameters.draw(&mut display, |index| {
+            let definition = &settings.definitions()[index];
+            match (&definition.kind, settings.value(index)) {
+                (SettingKind::Bool, SettingValue::Bool(value)) => String::from(if *value { "On" } else { "Off" }),
+                (SettingKind::Text { .. }, value) => value.to_text(),
+                (_, value) => format!("< {} {} >", value.to_text(), definition.unit),
+            }
+        });
+    }
+}
+
+impl PageInterface for PageSettings {
+    fn page_view_init(&mut self) {}
+
+    fn page_enter(&mut self) {
+        self.apply_layout();
+        self.page_view_draw();
+    }
+
+    fn process_key_input(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            debug!("{:?}", first_key);
+            self.dispatch_key(&Self::key_bindings(), first_key);
+        }
+    }
+
+    fn process_key_input_block_menu(&mut self) {}
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                match self.parameters.touch_input(x, y) {
+                    Some(0) => self.activate_setting(),
+                    Some(dir) => self.change_setting(dir as i32),
+                    None => {},
+                }
+            }
+        }
+    }
+
+    fn navigate_vertical(&mut self, dir: isize) {
+        if self.parameters.navigate(dir) {
+            self.page_view_draw();
+        }
+    }
+
+    fn navigate_horizontal(&mut self, dir: isize) {
+        self.change_setting(dir as i32);
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
+    }
+
+    fn open_menu(&mut self) {
+        self.activate_setting();
+    }
+
+    fn call_block_menu(&mut self) {}
+
+    fn call_page(&mut self) {
+        self.page_view_draw();
+    }
+
+    fn help(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Page", &Self::key_bindings())]
+    }
+
+    fn page_view_update(&mut self) {
+        self.process_text_input();
+        self.process_key_input();
+        self.process_touch_input();
+    }
+}
@@ -0,0 +1,274 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use log::debug;
+use crate::model::core::Core;
+use crate::model::spectrum::{magnitude_spectrum, peak_frequency, remove_mean, WINDOW_FUNCTIONS};
+use crate::view::display::display::Display;
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::HelpSection;
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::layout::Layout;
+use crate::view::view_main::{PageInterface, PageNavigator};
+use crate::view::widget::parameter_list::ParameterList;
+
+const PARAMETER_NAMES: [&str; 4] = ["Track", "FFT Size", "Window", "Scale"];
+const READOUT_HEIGHT: usize = 20;
+const AXIS_HEIGHT: usize = 20;
+/// FFT sizes from 16 to 1024 samples, shorter tracks are zero padded
+const MIN_FFT_SIZE_LOG2: u32 = 4;
+const MAX_FFT_SIZE_LOG2: u32 = 10;
+const DEFAULT_FFT_SIZE_LOG2: u32 = 8;
+/// Bottom of the logarithmic scale, 0 dB is a full scale sine
+const MIN_DB: f32 = -80.0;
+
+/// # Page: Spectrum
+/// Magnitude per FFT bin of the window ending at the playback position, for tuning the operator rack and checking generators.
+/// The peak frequency is read out from the core sample rate.
+/// `Up`/`Down` select a parameter and `Left`/`Right` change it.
+pub struct PageSpectrum {
+    display_ref: Rc<RefCell<Display>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    track_number: usize,
+    track_index: usize,
+    fft_size_log2: u32,
+    window_function: usize,
+    log_scale: bool,
+    parameters: ParameterList,
+    readout_rect: [usize; 4],
+    plot_rect: [usize; 4],
+    axis_rect: [usize; 4],
+    /// Magnitudes of the last computed window, empty when the track has no data
+    spectrum: Vec<f32>,
+    last_time: Option<usize>,
+    redraw: bool,
+}
+
+impl PageSpectrum {
+    pub fn new(display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>,
+               core: Rc<RefCell<Core>>) -> Self {
+        let track_number = core.borrow().track_number();
+        PageSpectrum {
+            display_ref,
+            input_manager,
+            navigator,
+            core,
+            track_number,
+            track_index: 0,
+            fft_size_log2: DEFAULT_FFT_SIZE_LOG2,
+            window_function: 1,
+            log_scale: true,
+            parameters: ParameterList::new(&PARAMETER_NAMES),
+            readout_rect: [0, 0, 0, 0],
+            plot_rect: [0, 0, 0, 0],
+            axis_rect: [0, 0, 0, 0],
+            spectrum: Vec::new(),
+            last_time: None,
+            redraw: true,
+        }
+    }
+
+    /// Parameters on the left, readout, spectrum and frequency axis on the right
+    fn layout() -> Layout {
+        ParameterList::page_layout(Layout::column(vec![
+            Layout::slot("readout").size(READOUT_HEIGHT),
+            Layout::slot("plot"),
+            Layout::slot("axis").size(AXIS_HEIGHT),
+        ]))
+    }
+
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let rects = Self::layout().compute([0, 0, page_size[0], page_size[1]]);
+        self.parameters.set_rect(rects.get("parameters").unwrap_or([0, 0, 0, 0]));
+        self.readout_rect = rects.get("readout").unwrap_or([0, 0, 0, 0]);
+        self.plot_rect = rects.get("plot").unwrap_or([0, 0, 0, 0]);
+        self.axis_rect = rects.get("axis").unwrap_or([0, 0, 0, 0]);
+    }
+
+    fn fft_size(&self) -> usize {
+        1 << self.fft_size_log2
+    }
+
+    fn parameter_value(&self, index: usize) -> String {
+        match index {
+            0 => self.core.borrow().wave_container.get_track_name(self.track_index).to_string(),
+            1 => format!("{} smp", self.fft_size()),
+            2 => String::from(WINDOW_FUNCTIONS[self.window_function].name()),
+            _ => String::from(if self.log_scale { "Log (dB)" } else { "Linear" }),
+        }
+    }
+
+    fn change_parameter(&mut self, dir: isize) {
+        match self.parameters.selected() {
+            0 => self.track_index = (self.track_index as isize + dir).rem_euclid(self.track_number as isize) as usize,
+            1 => self.fft_size_log2 = (self.fft_size_log2 as isize + dir).clamp(MIN_FFT_SIZE_LOG2 as isize, MAX_FFT_SIZE_LOG2 as isize) as u32,
+            2 => self.window_function = (self.window_function as isize + dir).rem_euclid(WINDOW_FUNCTIONS.len() as isize) as usize,
+            _ => self.log_scale = !self.log_scale,
+        }
+        debug!("Spectrum: {} = {}", self.parameters.selected_name(), self.parameter_value(self.parameters.selected()));
+        self.last_time = None;
+        self.redraw = true;
+    }
+
+    /// Recomputes the spectrum when the playback position moved or a parameter changed, returns true when it did
+    fn update_spectrum(&mut self) -> bool {
+        let core = self.core.borrow();
+        if self.last_time == Some(core.time()) {
+            return false;
+        }
+        self.last_time = Some(core.time());
+        let track = core.wave_container.wave_track[self.track_index].borrow();
+        if track.is_empty() {
+            self.spectrum.clear();
+            return true;
+        }
+        // normalized to the track maximum, so 0 dB is a sine over the full range of the track
+        let max_abs = track.iter().map(|value| value.saturating_abs()).max().unwrap_or(1).max(1) as f32;
+        let track_length = core.track_length() as isize;
+        let length = self.fft_size().min(core.track_length());
+        let mut samples: Vec<f32> = (0..length).rev().map(|age| {
+            let time = (core.time() as isize - age as isize).rem_euclid(track_length) as usize;
+            track[time % track.len()] as f32 / max_abs
+        }).collect();
+        remove_mean(&mut samples);
+        self.spectrum = magnitude_spectrum(&samples, WINDOW_FUNCTIONS[self.window_function], self.fft_size());
+        true
+    }
+
+    /// Height of a magnitude in the plot from 0.0 to 1.0, `reference` is the largest linear magnitude
+    fn scaled(&self, magnitude: f32, reference: f32) -> f32 {
+        if self.log_scale {
+            let db = 20.0 * magnitude.max(1e-9).log10();
+            ((db - MIN_DB) / -MIN_DB).clamp(0.0, 1.0)
+        } else {
+            (magnitude / reference).clamp(0.0, 1.0)
+        }
+    }
+
+    fn draw_spectrum(&self, display: &mut Display) {
+        let [x0, y0, x1, y1] = self.plot_rect;
+        display.draw_rectangle(x0, y0, x1, y1, (0, 0, 0), true);
+        display.draw_rectangle(x0, y0, x1, y1, (60, 60, 60), false);
+        let sample_rate = self.core.borrow().sample_rate();
+        let nyquist = sample_rate / 2.0;
+
+        let [axis_x0, axis_y0, axis_x1, _] = self.axis_rect;
+        for fraction in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            let x = axis_x0 + ((axis_x1 - axis_x0) as f32 * fraction) as usize;
+            display.draw_line(x, axis_y0, x, axis_y0 + 4, (200, 200, 200));
+            let label = format!("{:.1}", nyquist * fraction);
+            let label_x = (x.saturating_sub(display.text_width(&label, 1, 1, 1) / 2)).clamp(axis_x0, axis_x1);
+            display.text(&label, 1, label_x, axis_y0 + 6, 1, 1, (200, 200, 200));
+        }
+
+        let [readout_x0, readout_y0, _, _] = self.readout_rect;
+        let peak = match peak_frequency(&self.spectrum, sample_rate) {
+            Some(peak) => peak,
+            None => {
+                display.text("No signal on the track", 1, x0 + 10, y0 + 10, 1, 1, (255, 0, 0));
+                return;
+            },
+        };
+        let (peak_frequency, peak_magnitude) = peak;
+        let peak_text = if self.log_scale {
+            format!("Peak {:.2} Hz  {:.1} dB", peak_frequency, 20.0 * peak_magnitude.max(1e-9).log10())
+        } else {
+            format!("Peak {:.2} Hz  {:.3}", peak_frequency, peak_magnitude)
+        };
+        display.text(&peak_text, 1, readout_x0, readout_y0 + 4, 1, 1, (255, 200, 0));
+
+        // one column per pixel, showing the strongest bin that falls into it
+        let width = x1 - x0 - 2;
+        let height = (y1 - y0 - 2) as f32;
+        let bin_number = self.spectrum.len();
+        let reference = self.spectrum.iter().skip(1).cloned().fold(f32::EPSILON, f32::max);
+        for px in 0..width {
+            let first_bin = px * bin_number / width;
+            let last_bin = ((px + 1) * bin_number / width).max(first_bin + 1).min(bin_number);
+            let magnitude = self.spectrum[first_bin..last_bin].iter().cloned().fold(0.0, f32::max);
+            let bar_height = (self.scaled(magnitude, reference) * height) as usize;
+            if bar_height > 0 {
+                display.draw_line(x0 + 1 + px, y1 - 1, x0 + 1 + px, y1 - 1 - bar_height, (0, 200, 255));
+            }
+        }
+        let peak_x = x0 + 1 + ((peak_frequency / nyquist).clamp(0.0, 1.0) * (width - 1) as f32) as usize;
+        display.draw_line(peak_x, y0 + 1, peak_x, y1 - 1, (255, 200, 0));
+    }
+
+    fn page_view_draw(&mut self) {
+        let mut display = self.display_ref.borrow_mut();
+        display.clean();
+        display.text("Spectrum", 1, 10, 8, 1, 1, (255, 255, 255));
+        self.parameters.draw(&mut display, |index| format!("< {} >", self.parameter_value(index)));
+        self.draw_spectrum(&mut display);
+    }
+}
+
+impl PageInterface for PageSpectrum {
+    fn page_view_init(&mut self) {}
+
+    fn page_enter(&mut self) {
+        // follows the track selected in the other pages
+        self.track_index = self.core.borrow().wave_container.get_selected_track();
+        self.apply_layout();
+        self.last_time = None;
+        self.update_spectrum();
+        self.page_view_draw();
+    }
+
+    fn process_key_input(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            debug!("{:?}", first_key);
+            self.dispatch_key(&ParameterList::key_bindings(), first_key);
+        }
+    }
+
+    fn process_key_input_block_menu(&mut self) {}
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                if let Some(dir) = self.parameters.touch_input(x, y) {
+                    self.change_parameter(dir);
+                }
+            }
+        }
+    }
+
+    fn navigate_vertical(&mut self, dir: isize) {
+        if self.parameters.navigate(dir) {
+            self.redraw = true;
+        }
+    }
+
+    fn navigate_horizontal(&mut self, dir: isize) {
+        self.change_parameter(dir);
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
+    }
+
+    fn call_block_menu(&mut self) {}
+
+    fn call_page(&mut self) {
+        self.page_view_draw();
+    }
+
+    fn help(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Page", &ParameterList::key_bindings())]
+    }
+
+    fn page_view_update(&mut self) {
+        self.process_key_input();
+        self.process_touch_input();
+        if self.update_spectrum() || self.redraw {
+            self.redraw = false;
+            self.page_view_draw();
+        }
+    }
+}
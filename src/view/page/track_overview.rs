@@ -0,0 +1,459 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use log::debug;
+use crate::model::core::{Core, CoreCommand};
+use crate::view::display::display::Display;
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::{HelpSection, KeyBinding, PageCommand};
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::layout::Layout;
+use crate::view::page::file_browser::shorten_end;
+use crate::view::view_main::{PageInterface, PageNavigator, rect_contains, track_loader_label, MENU_RECT};
+use crate::view::widget::menu::{menu_key_help, Menu, MenuItem, MenuResult};
+use crate::view::widget::notifier::Notifier;
+
+const TITLE_HEIGHT: usize = 20;
+/// Name, loader and indicator column left of the waveforms
+const INFO_WIDTH: usize = 90;
+const METER_WIDTH: usize = 14;
+/// Time axis below the tracks
+const AXIS_HEIGHT: usize = 20;
+const INDICATOR_SIZE: usize = 16;
+/// Meter level lost per frame, the meter falls back slowly after a peak
+const METER_DECAY: f32 = 0.04;
+
+#[derive(Clone, Debug)]
+enum OverviewAction {
+    ToggleMute,
+    ToggleRecordArm,
+    OpenWaveEditor,
+    OpenXyScope,
+    OpenSpectrum,
+    Core(CoreCommand),
+}
+
+/// Screen areas of one track row
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RowRects {
+    info: [usize; 4],
+    wave: [usize; 4],
+    meter: [usize; 4],
+}
+
+impl RowRects {
+    /// Mute and record indicators at the bottom of the info column
+    fn indicator_rects(&self) -> [[usize; 4]; 2] {
+        let [x0, _, _, y1] = self.info;
+        let y0 = y1.saturating_sub(INDICATOR_SIZE + 4);
+        [[x0 + 5, y0, x0 + 5 + INDICATOR_SIZE, y0 + INDICATOR_SIZE],
+         [x0 + 10 + INDICATOR_SIZE, y0, x0 + 10 + INDICATOR_SIZE * 2, y0 + INDICATOR_SIZE]]
+    }
+}
+
+/// Waveform and state of one track, compared between frames to skip redraws
+#[derive(Clone, Debug, PartialEq)]
+struct TrackRow {
+    name: String,
+    loader: String,
+    muted: bool,
+    recording: bool,
+    /// First, lowest and highest y of every column, relative to the wave rect
+    columns: Vec<[usize; 3]>,
+    /// Meter level from 0.0 to 1.0, relative to the largest value of the track
+    level: f32,
+}
+
+/// # Page: Track Overview
+/// All tracks stacked on a shared time axis of one core track length, with a shared playhead,
+/// level meters and mute and record indicators. `Up`/`Down` select a track, `Menu` opens the track menu.
+pub struct PageTrackOverview {
+    display_ref: Rc<RefCell<Display>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    notifier: Rc<RefCell<Notifier>>,
+    track_number: usize,
+    row_rects: Vec<RowRects>,
+    axis_rect: [usize; 4],
+    rows: Vec<TrackRow>,
+    track_length: usize,
+    playhead: usize,
+    selected_track: usize,
+    menu: Option<Menu<OverviewAction>>,
+    redraw: bool,
+}
+
+impl PageTrackOverview {
+    pub fn new(display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>,
+               core: Rc<RefCell<Core>>, notifier: Rc<RefCell<Notifier>>) -> Self {
+        let track_number = core.borrow().track_number();
+        PageTrackOverview {
+            display_ref,
+            input_manager,
+            navigator,
+            core,
+            notifier,
+            track_number,
+            row_rects: Vec::new(),
+            axis_rect: [0, 0, 0, 0],
+            rows: Vec::new(),
+            track_length: 0,
+            playhead: 0,
+            selected_track: 0,
+            menu: None,
+            redraw: true,
+        }
+    }
+
+    /// One row per track, the time axis below lines up with the waveforms
+    fn layout(track_number: usize) -> Layout {
+        let rows = (0..track_number).map(|i| Layout::row(vec![
+            Layout::slot(&format!("info_{}", i)).size(INFO_WIDTH),
+            Layout::slot(&format!("wave_{}", i)),
+            Layout::slot(&format!("meter_{}", i)).size(METER_WIDTH),
+        ]).spacing(6)).collect();
+        Layout::column(vec![
+            Layout::space().size(TITLE_HEIGHT),
+            Layout::column(rows).spacing(8),
+            Layout::row(vec![
+                Layout::space().size(INFO_WIDTH),
+                Layout::slot("axis"),
+                Layout::space().size(METER_WIDTH),
+            ]).size(AXIS_HEIGHT).spacing(6),
+        ]).padding(10).spacing(4)
+    }
+
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let rects = Self::layout(self.track_number).compute([0, 0, page_size[0], page_size[1]]);
+        let rect = |name: String| rects.get(&name).unwrap_or([0, 0, 0, 0]);
+        self.row_rects = (0..self.track_number).map(|i| RowRects {
+            info: rect(format!("info_{}", i)),
+            wave: rect(format!("wave_{}", i)),
+            meter: rect(format!("meter_{}", i)),
+        }).collect();
+        self.axis_rect = rect(String::from("axis"));
+    }
+
+    fn track_row(core: &Core, track_index: usize, wave_size: [usize; 2], previous_level: f32) -> TrackRow {
+        let wave_container = &core.wave_container;
+        let track = wave_container.wave_track[track_index].borrow();
+        let [width, height] = wave_size;
+        let max_abs = track.iter().map(|value| value.saturating_abs()).max().unwrap_or(1).max(1);
+        let value_to_y = |value: i32| {
+            let half_height = height as f32 / 2.0;
+            (half_height - value as f32 / max_abs as f32 * (half_height - 2.0)).clamp(0.0, height as f32) as usize
+        };
+
+        // the x axis is one core track length for every track, longer tracks are cut
+        let samples_per_pixel = core.track_length() as f32 / width.max(1) as f32;
+        let shown_samples = track.len().min(core.track_length());
+        let mut columns = Vec::new();
+        for px in 0..=width {
+            let start = (px as f32 * samples_per_pixel) as usize;
+            if start >= shown_samples {
+                break;
+            }
+            let end = (((px + 1) as f32 * samples_per_pixel) as usize).clamp(start + 1, shown_samples);
+            let bin = &track[start..end];
+            columns.push([value_to_y(bin[0]), value_to_y(*bin.iter().max().unwrap()), value_to_y(*bin.iter().min().unwrap())]);
+        }
+
+        // the core reads shorter tracks in a loop
+        let level = if track.is_empty() || wave_container.is_muted(track_index) {
+            0.0
+        } else {
+            track[core.time() % track.len()].saturating_abs() as f32 / max_abs as f32
+        };
+        TrackRow {
+            name: wave_container.get_track_name(track_index).to_string(),
+            loader: track_loader_label(&core.data_loader_container.track_loader[track_index]),
+            muted: wave_container.is_muted(track_index),
+            recording: wave_container.is_recording(track_index),
+            columns,
+            level: level.max(previous_level - METER_DECAY),
+        }
+    }
+
+    /// Reads all tracks from the core, returns true when anything visible changed
+    fn read_tracks(&mut self) -> bool {
+        let (rows, track_length, playhead) = {
+            let core = self.core.borrow();
+            let rows: Vec<TrackRow> = self.row_rects.iter().enumerate().map(|(track_index, rects)| {
+                let previous_level = self.rows.get(track_index).map_or(0.0, |row| row.level);
+                let wave_size = [rects.wave[2] - rects.wave[0], rects.wave[3] - rects.wave[1]];
+                Self::track_row(&core, track_index, wave_size, previous_level)
+            }).collect();
+            (rows, core.track_length(), core.time())
+        };
+        let changed = rows != self.rows || track_length != self.track_length || playhead != self.playhead;
+        self.rows = rows;
+        self.track_length = track_length;
+        self.playhead = playhead;
+        changed
+    }
+
+    fn execute_core_command(&mut self, command: CoreCommand) {
+        let result = self.core.borrow_mut().execute(command);
+        if let Err(e) = result {
+            self.notifier.borrow_mut().error(&e);
+        }
+    }
+
+    fn execute(&mut self, action: OverviewAction) {
+        let track_index = self.selected_track;
+        match action {
+            OverviewAction::ToggleMute => {
+                let muted = !self.core.borrow().wave_container.is_muted(track_index);
+                self.execute_core_command(CoreCommand::SetMuted { track_index, muted });
+            },
+            OverviewAction::ToggleRecordArm => {
+                let recording = !self.core.borrow().wave_container.is_recording(track_index);
+                self.execute_core_command(CoreCommand::SetRecording { track_index, recording });
+            },
+            OverviewAction::OpenWaveEditor => {
+                self.execute_core_command(CoreCommand::SelectTrack(track_index));
+                self.navigator.borrow_mut().switch_to("wave_editor");
+            },
+            OverviewAction::OpenXyScope => self.navigator.borrow_mut().push("xy_scope"),
+            OverviewAction::OpenSpectrum => {
+                self.execute_core_command(CoreCommand::SelectTrack(track_index));
+                self.navigator.borrow_mut().push("spectrum");
+            },
+            OverviewAction::Core(command) => self.execute_core_command(command),
+        }
+        self.redraw = true;
+    }
+
+    /// Keys of the page, dispatched by `process_key_input` and listed by the help overlay
+    fn key_bindings() -> Vec<KeyBinding<PageCommand<OverviewAction>>> {
+        vec![
+            KeyBinding::new("Up", "Previous track", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next track", PageCommand::Vertical(1)),
+            KeyBinding::new("Menu", "Track menu", PageCommand::OpenMenu),
+            KeyBinding::new("Play", "Play / stop", PageCommand::Page(OverviewAction::Core(CoreCommand::TogglePlay))),
+            KeyBinding::new("Page1", "Data loader", PageCommand::SwitchTo("data_loader")),
+            KeyBinding::new("Page2", "Wave editor", PageCommand::SwitchTo("wave_editor")),
+            KeyBinding::new("Page3", "Operator rack", PageCommand::SwitchTo("operator_rack")),
+            KeyBinding::new("Page4", "Settings", PageCommand::Push("settings")),
+            KeyBinding::new("Back", "Back", PageCommand::Pop),
+        ]
+    }
+
+    fn handle_menu_result(&mut self, result: MenuResult<OverviewAction>) {
+        match result {
+            MenuResult::Selected(action) => {
+                debug!("Menu Executed: {:?}", action);
+                self.menu = None;
+                self.execute(action);
+            },
+            MenuResult::Closed => {
+                self.menu = None;
+                self.redraw = true;
+            },
+            MenuResult::None => {},
+        }
+    }
+
+    fn page_view_draw(&mut self) {
+        let mut display = self.display_ref.borrow_mut();
+        display.clean();
+        display.text("Track Overview", 1, 10, 8, 1, 1, (255, 255, 255));
+
+        for (index, (row, rects)) in self.rows.iter().zip(self.row_rects.iter()).enumerate() {
+            // name, loader and indicators
+            let [x0, y0, x1, y1] = rects.info;
+            let color = if index == self.selected_track { (100,30,30) } else { (30,30,30) };
+            display.draw_rectangle(x0, y0, x1, y1, color, true);
+            let max_chars = INFO_WIDTH / 6;
+            display.text(&shorten_end(&row.name, max_chars), 1, x0 + 5, y0 + 3, 1, 1, (0, 255, 0));
+            display.text(&shorten_end(&row.loader, max_chars), 1, x0 + 5, y0 + 20, 1, 1, (200, 200, 200));
+            let [mute_rect, record_rect] = rects.indicator_rects();
+            for (rect, label, active, active_color) in [(mute_rect, "M", row.muted, (255, 200, 0)), (record_rect, "R", row.recording, (255, 0, 0))] {
+                let background = if active { active_color } else { (60, 60, 60) };
+                display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], background, true);
+                display.text(label, 1, rect[0] + 4, rect[1], 1, 1, (255, 255, 255));
+            }
+
+            // waveform on the shared time axis
+            let [x0, y0, x1, y1] = rects.wave;
+            display.draw_rectangle(x0, y0, x1, y1, (0, 0, 0), true);
+            display.draw_rectangle(x0, y0, x1, y1, (60, 60, 60), false);
+            let zero_y = y0 + (y1 - y0) / 2;
+            display.draw_line(x0, zero_y, x1, zero_y, (40, 40, 40));
+            let wave_color = if row.muted { (90, 90, 90) } else { (0, 200, 255) };
+            let mut previous: Option<(usize, usize)> = None;
+            for (px, [y, y_max, y_min]) in row.columns.iter().enumerate() {
+                if let Some((previous_x, previous_y)) = previous {
+                    display.draw_line(previous_x, previous_y, x0 + px, y0 + y, wave_color);
+                }
+                if y_max != y_min {
+                    display.draw_line(x0 + px, y0 + y_max, x0 + px, y0 + y_min, wave_color);
+                }
+                previous = Some((x0 + px, y0 + y));
+            }
+
+            // level meter, filled from the bottom
+            let [x0, y0, x1, y1] = rects.meter;
+            display.draw_rectangle(x0, y0, x1, y1, (60, 60, 60), false);
+            let meter_height = ((y1 - y0 - 2) as f32 * row.level.clamp(0.0, 1.0)) as usize;
+            if meter_height > 0 {
+                let meter_color = if row.muted {
+                    (90, 90, 90)
+                } else if row.level > 0.9 {
+                    (255, 0, 0)
+                } else if row.level > 0.7 {
+                    (255, 200, 0)
+                } else {
+                    (0, 255, 0)
+                };
+                display.draw_rectangle(x0 + 2, y1 - 1 - meter_height, x1 - 2, y1 - 1, meter_color, true);
+            }
+        }
+
+        // shared playhead and time axis
+        let [x0, y0, x1, _] = self.axis_rect;
+        let width = x1 - x0;
+        if self.track_length > 0 {
+            let playhead_x = x0 + self.playhead * width / self.track_length;
+            for rects in self.row_rects.iter() {
+                display.draw_line(playhead_x, rects.wave[1], playhead_x, rects.wave[3], (255, 255, 0));
+            }
+        }
+        display.draw_line(x0, y0, x1, y0, (100, 100, 100));
+        for tick in 0..=4 {
+            let x = x0 + tick * width / 4;
+            display.draw_line(x, y0, x, y0 + 4, (100, 100, 100));
+        }
+        display.text("0", 1, x0, y0 + 6, 1, 1, (200, 200, 200));
+        let length_text = self.track_length.to_string();
+        let length_width = display.text_width(&length_text, 1, 1, 1);
+        display.text(&length_text, 1, x1.saturating_sub(length_width), y0 + 6, 1, 1, (200, 200, 200));
+    }
+}
+
+impl PageInterface for PageTrackOverview {
+    fn page_view_init(&mut self) {}
+
+    fn page_enter(&mut self) {
+        self.menu = None;
+        self.selected_track = self.core.borrow().wave_container.get_selected_track();
+        self.apply_layout();
+        self.read_tracks();
+        self.page_view_draw();
+    }
+
+    fn process_key_input(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            debug!("{:?}", first_key);
+            if let Some(action) = self.dispatch_key(&Self::key_bindings(), first_key) {
+                self.execute(action);
+            }
+        }
+    }
+
+    fn process_key_input_block_menu(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let (Some(first_key), Some(menu)) = (key.get(0), &mut self.menu) {
+            let result = menu.key_input(first_key);
+            self.handle_menu_result(result);
+        }
+    }
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                if let Some(menu) = &mut self.menu {
+                    let result = menu.touch_input(MENU_RECT, x, y);
+                    self.handle_menu_result(result);
+                    continue;
+                }
+                // the indicators toggle right away, a tap on the selected track opens its menu
+                let hit = self.row_rects.iter().position(|rects| {
+                    rect_contains([rects.info[0], rects.info[1], rects.meter[2], rects.info[3]], x, y)
+                });
+                if let Some(track_index) = hit {
+                    let [mute_rect, record_rect] = self.row_rects[track_index].indicator_rects();
+                    self.selected_track = track_index;
+                    if rect_contains(mute_rect, x, y) {
+                        self.execute(OverviewAction::ToggleMute);
+                    } else if rect_contains(record_rect, x, y) {
+                        self.execute(OverviewAction::ToggleRecordArm);
+                    } else if self.core.borrow().wave_container.get_selected_track() == track_index {
+                        self.open_menu();
+                    } else {
+                        self.execute(OverviewAction::Core(CoreCommand::SelectTrack(track_index)));
+                    }
+                }
+            }
+        }
+    }
+
+    fn navigate_vertical(&mut self, dir: isize) {
+        let new_index = self.selected_track as isize + dir;
+        if new_index >= 0 && new_index < self.track_number as isize {
+            self.selected_track = new_index as usize;
+            self.execute(OverviewAction::Core(CoreCommand::SelectTrack(self.selected_track)));
+        }
+    }
+
+    fn navigate_horizontal(&mut self, _dir: isize) {}
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
+    }
+
+    fn open_menu(&mut self) {
+        let row = match self.rows.get(self.selected_track) {
+            Some(row) => row,
+            None => return,
+        };
+        self.menu = Some(Menu::new(&row.name, vec![
+            MenuItem::new_action(if row.muted { "Unmute" } else { "Mute" }, OverviewAction::ToggleMute),
+            MenuItem::new_action(if row.recording { "Disarm Recording" } else { "Arm Recording" }, OverviewAction::ToggleRecordArm),
+            MenuItem::new_action("Open In Wave Editor", OverviewAction::OpenWaveEditor),
+            MenuItem::new_action("Open XY Scope", OverviewAction::OpenXyScope),
+            MenuItem::new_action("Open Spectrum", OverviewAction::OpenSpectrum),
+        ]));
+    }
+
+    fn call_block_menu(&mut self) {
+        self.open_menu();
+    }
+
+    fn call_page(&mut self) {
+        self.page_view_draw();
+    }
+
+    fn help(&self) -> Vec<HelpSection> {
+        match &self.menu {
+            Some(menu) => vec![menu_key_help(), menu.help_section("Menu: Track")],
+            None => vec![HelpSection::from_bindings("Page", &Self::key_bindings())],
+        }
+    }
+
+    fn page_view_update(&mut self) {
+        if self.menu.is_some() {
+            self.process_key_input_block_menu();
+            self.process_touch_input();
+            if let Some(menu) = &mut self.menu {
+                menu.draw(&mut self.display_ref.borrow_mut(), MENU_RECT);
+            }
+            return;
+        }
+
+        self.process_key_input();
+        self.process_touch_input();
+        if self.menu.is_some() {
+            self.page_view_draw();
+            return;
+        }
+        // playhead and meters move with the core
+        if self.read_tracks() || self.redraw {
+            self.redraw = false;
+            self.page_view_draw();
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use log::debug;
+use crate::model::core::Core;
+use crate::view::display::display::Display;
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::HelpSection;
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::layout::Layout;
+use crate::view::view_main::{PageInterface, PageNavigator};
+use crate::view::widget::parameter_list::ParameterList;
+
+const PARAMETER_NAMES: [&str; 5] = ["X Track", "Y Track", "Intensity", "Window", "Persistence"];
+/// Step of the Window parameter, in samples
+const WINDOW_STEP: usize = 10;
+/// Samples plotted per frame, ending at the playback position
+const DEFAULT_WINDOW: usize = 60;
+/// Frames of the trace kept on screen for each persistence setting
+const PERSISTENCE_LEVELS: [(&str, usize); 4] = [("Off", 1), ("Short", 4), ("Medium", 8), ("Long", 16)];
+/// Darkest point of the intensity track, the trace stays visible
+const MIN_INTENSITY: f32 = 0.2;
+
+/// One plotted sample in plot coordinates, `intensity` from 0.0 to 1.0
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ScopePoint {
+    x: usize,
+    y: usize,
+    intensity: f32,
+}
+
+/// # Page: XY Scope
+/// Plots the current window of one track against another, e.g. for Lissajous figures of two sensors.
+/// Older traces fade out over the persistence frames, an optional third track sets the point intensity.
+/// `Up`/`Down` select a parameter and `Left`/`Right` change it.
+pub struct PageXyScope {
+    display_ref: Rc<RefCell<Display>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    track_number: usize,
+    x_track: usize,
+    y_track: usize,
+    intensity_track: Option<usize>,
+    window: usize,
+    persistence: usize,
+    parameters: ParameterList,
+    plot_rect: [usize; 4],
+    /// Traces of the recent frames, oldest first
+    traces: VecDeque<Vec<ScopePoint>>,
+    last_time: Option<usize>,
+    redraw: bool,
+}
+
+impl PageXyScope {
+    pub fn new(display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>,
+               core: Rc<RefCell<Core>>) -> Self {
+        let track_number = core.borrow().track_number();
+        PageXyScope {
+            display_ref,
+            input_manager,
+            navigator,
+            core,
+            track_number,
+            x_track: 0,
+            y_track: 1.min(track_number.saturating_sub(1)),
+            intensity_track: None,
+            window: DEFAULT_WINDOW,
+            persistence: 2,
+            parameters: ParameterList::new(&PARAMETER_NAMES),
+            plot_rect: [0, 0, 0, 0],
+            traces: VecDeque::new(),
+            last_time: None,
+            redraw: true,
+        }
+    }
+
+    /// Parameters on the left, the square plot on the right
+    fn layout() -> Layout {
+        ParameterList::page_layout(Layout::slot("plot"))
+    }
+
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let rects = Self::layout().compute([0, 0, page_size[0], page_size[1]]);
+        self.parameters.set_rect(rects.get("parameters").unwrap_or([0, 0, 0, 0]));
+        // both axes get the same scale
+        let [x0, y0, x1, y1] = rects.get("plot").unwrap_or([0, 0, 0, 0]);
+        let side = (x1 - x0).min(y1 - y0);
+        let plot_x0 = x0 + (x1 - x0 - side) / 2;
+        let plot_y0 = y0 + (y1 - y0 - side) / 2;
+        self.plot_rect = [plot_x0, plot_y0, plot_x0 + side, plot_y0 + side];
+    }
+
+    fn parameter_value(&self, index: usize) -> String {
+        let track_name = |track_index: usize| self.core.borrow().wave_container.get_track_name(track_index).to_string();
+        match index {
+            0 => track_name(self.x_track),
+            1 => track_name(self.y_track),
+            2 => self.intensity_track.map_or(String::from("Off"), track_name),
+            3 => format!("{} smp", self.window),
+            _ => String::from(PERSISTENCE_LEVELS[self.persistence].0),
+        }
+    }
+
+    fn change_parameter(&mut self, dir: isize) {
+        let track_number = self.track_number as isize;
+        match self.parameters.selected() {
+            0 => self.x_track = (self.x_track as isize + dir).rem_euclid(track_number) as usize,
+            1 => self.y_track = (self.y_track as isize + dir).rem_euclid(track_number) as usize,
+            2 => {
+                // Off comes before the first track
+                let current = self.intensity_track.map_or(0, |track_index| track_index as isize + 1);
+                let next = (current + dir).rem_euclid(track_number + 1);
+                self.intensity_track = if next == 0 { None } else { Some(next as usize - 1) };
+            },
+            3 => {
+                let max_window = self.core.borrow().track_length().max(WINDOW_STEP);
+                self.window = (self.window as isize + dir * WINDOW_STEP as isize).clamp(WINDOW_STEP as isize, max_window as isize) as usize;
+            },
+            _ => self.persistence = (self.persistence as isize + dir).clamp(0, PERSISTENCE_LEVELS.len() as isize - 1) as usize,
+        }
+        debug!("XY scope: {} = {}", self.parameters.selected_name(), self.parameter_value(self.parameters.selected()));
+        // the old traces belong to other settings
+        self.traces.clear();
+        self.last_time = None;
+        self.redraw = true;
+    }
+
+    /// Samples of the window ending at the playback position, mapped into the plot
+    fn read_trace(&self, core: &Core) -> Vec<ScopePoint> {
+        let wave_container = &core.wave_container;
+        let x_track = wave_container.wave_track[self.x_track].borrow();
+        let y_track = wave_container.wave_track[self.y_track].borrow();
+        if x_track.is_empty() || y_track.is_empty() {
+            return Vec::new();
+        }
+        let intensity_track = self.intensity_track.map(|track_index| wave_container.wave_track[track_index].borrow());
+        let intensity_range = intensity_track.as_ref().filter(|track| !track.is_empty()).map(|track| {
+            let min = *track.iter().min().unwrap();
+            let max = *track.iter().max().unwrap();
+            (min, (max as i64 - min as i64).max(1))
+        });
+
+        let max_abs = |track: &[i32]| track.iter().map(|value| value.saturating_abs()).max().unwrap_or(1).max(1) as f32;
+        let (x_max_abs, y_max_abs) = (max_abs(&x_track), max_abs(&y_track));
+        let [x0, y0, x1, y1] = self.plot_rect;
+        let half_side = (x1 - x0) as f32 / 2.0 - 4.0;
+        let (center_x, center_y) = ((x0 + x1) as f32 / 2.0, (y0 + y1) as f32 / 2.0);
+
+        let track_length = core.track_length() as isize;
+        let window = self.window.min(core.track_length());
+        (0..window).rev().map(|age| {
+            // the core reads every track in a loop of its own length
+            let time = (core.time() as isize - age as isize).rem_euclid(track_length) as usize;
+            let x_value = x_track[time % x_track.len()] as f32 / x_max_abs;
+            let y_value = y_track[time % y_track.len()] as f32 / y_max_abs;
+            let intensity = match (&intensity_track, intensity_range) {
+                (Some(track), Some((min, range))) => {
+                    let value = track[time % track.len()] as i64;
+                    MIN_INTENSITY + (1.0 - MIN_INTENSITY) * (value - min as i64) as f32 / range as f32
+                },
+                _ => 1.0,
+            };
+            ScopePoint {
+                x: (center_x + x_value * half_side) as usize,
+                y: (center_y - y_value * half_side) as usize,
+                intensity,
+            }
+        }).collect()
+    }
+
+    /// Adds the trace of a new playback position, returns true when the plot has to be redrawn
+    fn update_traces(&mut self) -> bool {
+        let time = self.core.borrow().time();
+        if self.last_time == Some(time) {
+            return false;
+        }
+        self.last_time = Some(time);
+        let trace = self.read_trace(&self.core.borrow());
+        self.traces.push_back(trace);
+        while self.traces.len() > PERSISTENCE_LEVELS[self.persistence].1 {
+            self.traces.pop_front();
+        }
+        true
+    }
+
+    fn draw_plot(&self, display: &mut Display) {
+        let [x0, y0, x1, y1] = self.plot_rect;
+        display.draw_rectangle(x0, y0, x1, y1, (0, 0, 0), true);
+        display.draw_rectangle(x0, y0, x1, y1, (60, 60, 60), false);
+        let (center_x, center_y) = ((x0 + x1) / 2, (y0 + y1) / 2);
+        display.draw_line(x0, center_y, x1, center_y, (40, 40, 40));
+        display.draw_line(center_x, y0, center_x, y1, (40, 40, 40));
+
+        if self.traces.back().map_or(true, |trace| trace.is_empty()) {
+            display.text("No data on the X or Y track", 1, x0 + 10, y0 + 10, 1, 1, (255, 0, 0));
+            return;
+        }
+        // older traces are darker, like the phosphor of an analog scope
+        let trace_number = self.traces.len();
+        for (age_index, trace) in self.traces.iter().enumerate() {
+            let fade = (age_index + 1) as f32 / trace_number as f32;
+            for pair in trace.windows(2) {
+                let brightness = fade * pair[1].intensity;
+                let color = ((80.0 * brightness) as u8, (255.0 * brightness) as u8, (120.0 * brightness) as u8);
+                display.draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, color);
+            }
+        }
+    }
+
+    fn page_view_draw(&mut self) {
+        let mut display = self.display_ref.borrow_mut();
+        display.clean();
+        display.text("XY Scope", 1, 10, 8, 1, 1, (255, 255, 255));
+        self.parameters.draw(&mut display, |index| format!("< {} >", self.parameter_value(index)));
+        self.draw_plot(&mut display);
+    }
+}
+
+impl PageInterface for PageXyScope {
+    fn page_view_init(&mut self) {}
+
+    fn page_enter(&mut self) {
+        self.apply_layout();
+        self.traces.clear();
+        self.last_time = None;
+        self.update_traces();
+        self.page_view_draw();
+    }
+
+    fn process_key_input(&mut self) {
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            debug!("{:?}", first_key);
+            self.dispatch_key(&ParameterList::key_bindings(), first_key);
+        }
+    }
+
+    fn process_key_input_block_menu(&mut self) {}
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                if let Some(dir) = self.parameters.touch_input(x, y) {
+                    self.change_parameter(dir);
+                }
+            }
+        }
+    }
+
+    fn navigate_vertical(&mut self, dir: isize) {
+        if self.parameters.navigate(dir) {
+            self.redraw = true;
+        }
+    }
+
+    fn navigate_horizontal(&mut self, dir: isize) {
+        self.change_parameter(dir);
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
+    }
+
+    fn call_block_menu(&mut self) {}
+
+    fn call_page(&mut self) {
+        self.page_view_draw();
+    }
+
+    fn help(&self) -> Vec<HelpSection> {
+        vec![HelpSection::from_bindings("Page", &ParameterList::key_bindings())]
+    }
+
+    fn page_view_update(&mut self) {
+        self.process_key_input();
+        self.process_touch_input();
+        // the plot only changes when the playback position moves
+        if self.update_traces() || self.redraw {
+            self.redraw = false;
+            self.page_view_draw();
+        }
+    }
+}
@@ -2,9 +2,30 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::thread;
 use std::time::{Duration, Instant};
-use crate::view::display::display::Display;
-use crate::view::interaction::key_manager::KeyManager;
-use log::{debug,info};
+use crate::view::display::display::{Display, DisplayTheme};
+use crate::view::interaction::input_manager::InputManager;
+use crate::view::interaction::key_binding::{find_command, HelpSection, KeyBinding, PageCommand};
+use crate::view::interaction::touch_manager::TouchEvent;
+use crate::view::widget::menu::{menu_key_help, Menu, MenuItem, MenuResult};
+use crate::view::widget::text_input::TextInputResult;
+use crate::model::track_loader::{TrackLoader, WaveGenerateType};
+use crate::model::core::{Core, CoreCommand};
+use crate::const_parameter::{CORE_SAMPLE_RATE, DISPLAY_WIDTH, DISPLAY_HEIGHT};
+use crate::view::page::generator_editor::PageGeneratorEditor;
+use crate::view::page::operator_rack::PageOperatorRack;
+use crate::view::page::settings::PageSettings;
+use crate::view::page::track_overview::PageTrackOverview;
+use crate::view::page::xy_scope::PageXyScope;
+use crate::view::page::spectrum::PageSpectrum;
+use crate::settings::Settings;
+use crate::view::page::file_browser::{FileBrowserRequest, PageFileBrowser, shorten_end};
+use crate::view::widget::notifier::Notifier;
+use crate::view::widget::status_bar::{page_title, StatusBar, STATUS_BAR_HEIGHT};
+use crate::view::widget::help_overlay::HelpOverlay;
+use crate::view::layout::Layout;
+use crate::view::frame_timing::{FramePacer, FrameStats};
+use std::path::Path;
+use log::{debug,info,warn};
 
 
 /// # Main View Container
@@ -12,94 +33,522 @@ pub struct ViewContainer {
     loop_start_time: Instant,
     fps: Duration,
     display:Rc<RefCell<Display>>,
-    page_0: Page0DataLoader,
-    page_1: Page1WaveEditor,
-    page_index: Rc<RefCell<usize>>,
-    page_index_prvious: usize,
-    key_manager: Rc<RefCell<KeyManager>>,
+    pages: Vec<(String, Box<dyn PageInterface>)>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    input_manager: Rc<RefCell<InputManager>>,
+    core: Rc<RefCell<Core>>,
+    notifier: Rc<RefCell<Notifier>>,
+    settings: Rc<RefCell<Settings>>,
+    core_last_tick: Instant,
+    /// Core ticks owed to the replayed frames, the fraction carries over to the next frame
+    replay_ticks: f32,
+    /// Hosts without a backlight fail on every brightness change, warn about it once
+    brightness_warned: bool,
+    status_bar: Option<StatusBar>,
+    help_overlay: Option<HelpOverlay>,
+    frame_stats: FrameStats,
+    frame_stats_overlay: bool,
+    frame_pacer: Option<FramePacer>,
+    /// Whether the pages, dialogs or toasts drew anything this frame
+    frame_changed: bool,
 }
 
 impl ViewContainer {
-    pub fn new(fps:f32, track_number:usize) -> Self {
-        let display = Rc::new(RefCell::new(Display::new(480, 480, 480 * 4,4)));
+    pub fn new(fps:f32, track_number:usize, input_manager: InputManager, settings: Settings) -> Self {
+        let display = Rc::new(RefCell::new(Display::new(DISPLAY_WIDTH, DISPLAY_HEIGHT, DISPLAY_WIDTH * 4, 4)));
         let display_ref = display.clone();
-        let key_manager = Rc::new(RefCell::new(KeyManager::new()));
-        let page_index = Rc::new(RefCell::new(0));
-        let page_0 = Page0DataLoader::new(track_number, display_ref.clone(), key_manager.clone(), page_index.clone());
-        let page_1 = Page1WaveEditor::new(track_number, display_ref.clone(), key_manager.clone(), page_index.clone());
-
-        ViewContainer{
+        let input_manager = Rc::new(RefCell::new(input_manager));
+        let navigator = Rc::new(RefCell::new(PageNavigator::new()));
+        let core = Rc::new(RefCell::new(Core::new(track_number)));
+        let notifier = Rc::new(RefCell::new(Notifier::new()));
+        let settings = Rc::new(RefCell::new(settings));
+        let file_browser_request = Rc::new(RefCell::new(None));
+        let page_0 = Page0DataLoader::new(track_number, display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone(), notifier.clone(), file_browser_request.clone());
+        let page_1 = Page1WaveEditor::new(track_number, display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone(), notifier.clone());
+        let page_generator_editor = PageGeneratorEditor::new(display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone(), notifier.clone());
+        let page_operator_rack = PageOperatorRack::new(display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone(), notifier.clone());
+        let page_file_browser = PageFileBrowser::new(display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone(), notifier.clone(), file_browser_request, settings.clone());
+        let page_settings = PageSettings::new(display_ref.clone(), input_manager.clone(), navigator.clone(), notifier.clone(), settings.clone());
+        let page_track_overview = PageTrackOverview::new(display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone(), notifier.clone());
+        let page_xy_scope = PageXyScope::new(display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone());
+        let page_spectrum = PageSpectrum::new(display_ref.clone(), input_manager.clone(), navigator.clone(), core.clone());
+
+        let mut view_container = ViewContainer{
             loop_start_time: Instant::now(),
             fps: Duration::from_secs_f32(1.0 / fps),
             display,
-            page_0,
-            page_1,
-            key_manager,
-            page_index,
-            page_index_prvious: 0,
+            pages: Vec::new(),
+            navigator,
+            input_manager,
+            core,
+            notifier,
+            settings,
+            core_last_tick: Instant::now(),
+            replay_ticks: 0.0,
+            brightness_warned: false,
+            status_bar: None,
+            help_overlay: None,
+            frame_stats: FrameStats::new(),
+            frame_stats_overlay: false,
+            frame_pacer: None,
+            frame_changed: true,
+        };
+        view_container.register_page("data_loader", Box::new(page_0));
+        view_container.register_page("wave_editor", Box::new(page_1));
+        view_container.register_page("operator_rack", Box::new(page_operator_rack));
+        view_container.register_page("generator_editor", Box::new(page_generator_editor));
+        view_container.register_page("file_browser", Box::new(page_file_browser));
+        view_container.register_page("settings", Box::new(page_settings));
+        view_container.register_page("track_overview", Box::new(page_track_overview));
+        view_container.register_page("xy_scope", Box::new(page_xy_scope));
+        view_container.register_page("spectrum", Box::new(page_spectrum));
+        view_container
+    }
+
+    /// Adds a page reachable through the `PageNavigator`, the first registered page is the start page
+    pub fn register_page(&mut self, page_id: &str, page: Box<dyn PageInterface>) {
+        if self.pages.iter().any(|(id, _)| id == page_id) {
+            warn!("Page {} registered twice, ignored", page_id);
+            return;
         }
+        self.pages.push((page_id.to_string(), page));
+    }
+
+    fn page_mut(&mut self, page_id: &str) -> Option<&mut Box<dyn PageInterface>> {
+        self.pages.iter_mut().find(|(id, _)| id == page_id).map(|(_, page)| page)
+    }
+
+    /// Shows the status bar above the pages, call before `frame_init`
+    pub fn enable_status_bar (&mut self) {
+        self.status_bar = Some(StatusBar::new());
+    }
+
+    /// Draws render time, flush time and percentiles over the pages
+    pub fn enable_frame_stats_overlay (&mut self) {
+        self.frame_stats_overlay = true;
+    }
+
+    /// Lowers the frame rate under load and while nothing changes
+    pub fn enable_adaptive_frame_rate (&mut self) {
+        self.frame_pacer = Some(FramePacer::new(self.fps));
+    }
+
+    pub fn frame_stats (&self) -> &FrameStats {
+        &self.frame_stats
+    }
+
+    fn frame_interval (&self) -> Duration {
+        self.frame_pacer.as_ref().map_or(self.fps, |pacer| pacer.frame_interval())
+    }
+
+    /// Screen area of the pages, below the status bar when it is shown
+    fn page_area (&self) -> [usize; 4] {
+        match self.status_bar {
+            Some(_) => [0, STATUS_BAR_HEIGHT, DISPLAY_WIDTH, DISPLAY_HEIGHT],
+            None => [0, 0, DISPLAY_WIDTH, DISPLAY_HEIGHT],
+        }
+    }
+
+    /// Pages draw and receive touches relative to the page area
+    fn enter_page_area (&mut self) {
+        let page_area = self.page_area();
+        self.display.borrow_mut().set_viewport(page_area);
+        self.input_manager.borrow_mut().set_touch_viewport(page_area);
+    }
+
+    /// Back to screen coordinates for the status bar, dialogs and toasts
+    fn leave_page_area (&mut self) {
+        self.display.borrow_mut().reset_viewport();
+        self.input_manager.borrow_mut().reset_touch_viewport();
     }
 
     pub fn frame_init (&mut self) {
-        self.page_0.page_view_init();
-        self.page_1.page_view_init();
+        self.enter_page_area();
+        for (_, page) in self.pages.iter_mut() {
+            page.page_view_init();
+        }
+        if let Some((start_page_id, _)) = self.pages.first() {
+            let start_page_id = start_page_id.clone();
+            self.navigator.borrow_mut().stack.push(start_page_id.clone());
+            if let Some(page) = self.page_mut(&start_page_id) {
+                page.page_enter();
+            }
+        }
+        self.leave_page_area();
     }
 
     pub fn frame_start (&mut self) {
         self.loop_start_time = Instant::now();
         self.display.borrow_mut().frame_start();
+        // navigate before polling, so the keys of this frame use the key map of the page that receives them
+        self.enter_page_area();
+        self.apply_navigation();
+        self.leave_page_area();
+        if let Some(page_id) = self.navigator.borrow().current_page() {
+            self.input_manager.borrow_mut().set_page(page_id);
+        }
+        self.input_manager.borrow_mut().poll();
     }
 
     pub fn frame_main (&mut self) {
+        if self.settings.borrow_mut().take_changed() {
+            self.apply_settings();
+        }
+        self.core_update();
+        self.enter_page_area();
+
+        let current_page_id = self.navigator.borrow().current_page().map(|page_id| page_id.to_string());
+        let current_page_id = match current_page_id {
+            Some(page_id) => page_id,
+            None => {
+                self.leave_page_area();
+                return;
+            },
+        };
+        let dialog_open = self.notifier.borrow().is_dialog_open();
+        let text_input_open = self.notifier.borrow().is_text_input_open();
+        let modal_open = dialog_open || text_input_open;
+        if !modal_open && self.help_overlay.is_none() && self.input_manager.borrow_mut().take_key("Help") {
+            self.open_help(&current_page_id);
+        }
+        let help_open = self.help_overlay.is_some();
+        if !modal_open && !help_open {
+            if let Some(page) = self.page_mut(&current_page_id) {
+                page.page_view_update();
+            }
+        }
+        if self.notifier.borrow_mut().update_toasts() {
+            if let Some(page) = self.page_mut(&current_page_id) {
+                page.page_view_redraw();
+            }
+        }
+        self.leave_page_area();
+
+        if dialog_open {
+            self.dialog_update(&current_page_id);
+        } else if text_input_open {
+            self.text_input_update(&current_page_id);
+        } else if help_open {
+            self.help_update(&current_page_id);
+        }
+        self.notifier.borrow_mut().draw_toast(&mut self.display.borrow_mut(), TOAST_RECT);
+        // the status bar and the overlay change every frame, they do not count as a change
+        self.frame_changed = self.display.borrow_mut().take_dirty();
+        if let Some(status_bar) = &self.status_bar {
+            status_bar.draw(&mut self.display.borrow_mut(), &current_page_id, &self.core.borrow(), self.fps);
+        }
+        if self.frame_stats_overlay {
+            self.frame_stats.draw_overlay(&mut self.display.borrow_mut(), FRAME_STATS_RECT, self.frame_interval());
+        }
+        // drop the flag set by the overlays, or the next frame would always count as changed
+        self.display.borrow_mut().take_dirty();
+    }
 
-        let current_page_index = *self.page_index.borrow();
+    /// The open dialog takes all input, the page below is redrawn once it is answered
+    fn dialog_update (&mut self, current_page_id: &str) {
+        let keys = self.input_manager.borrow_mut().check_keys();
+        let touches = self.input_manager.borrow_mut().check_touches();
+        let mut closed = false;
+        {
+            let mut notifier = self.notifier.borrow_mut();
+            for key in keys.iter() {
+                closed |= notifier.dialog_key_input(key);
+            }
+            for touch in touches {
+                if let TouchEvent::Tap { x, y } = touch {
+                    closed |= notifier.dialog_touch_input(DIALOG_RECT, x, y);
+                }
+            }
+        }
+        if closed {
+            self.enter_page_area();
+            if let Some(page) = self.page_mut(current_page_id) {
+                page.page_view_redraw();
+            }
+            self.leave_page_area();
+        } else {
+            self.notifier.borrow().draw_dialog(&mut self.display.borrow_mut(), DIALOG_RECT);
+        }
+    }
 
-        if current_page_index != self.page_index_prvious {
-            match current_page_index {
-                0 => {
-                    self.page_0.page_view_back();
-                },
-                1 => {
-                    self.page_1.page_view_back();
-                },
-                _ => {todo!()},
+    /// The open text input takes all input like a dialog, the page picks up the text by its id
+    fn text_input_update (&mut self, current_page_id: &str) {
+        let keys = self.input_manager.borrow_mut().check_keys();
+        let touches = self.input_manager.borrow_mut().check_touches();
+        let mut closed = false;
+        {
+            let mut notifier = self.notifier.borrow_mut();
+            for key in keys.iter() {
+                closed |= notifier.text_input_key_input(key);
+            }
+            for touch in touches {
+                if let TouchEvent::Tap { x, y } = touch {
+                    closed |= notifier.text_input_touch_input(TEXT_INPUT_RECT, x, y);
+                }
+            }
+        }
+        if closed {
+            self.enter_page_area();
+            if let Some(page) = self.page_mut(current_page_id) {
+                page.page_view_redraw();
+            }
+            self.leave_page_area();
+        } else {
+            self.notifier.borrow().draw_text_input(&mut self.display.borrow_mut(), TEXT_INPUT_RECT);
+        }
+    }
+
+    /// Lists the bindings the current page reports, with the keys the key map binds to them
+    fn open_help (&mut self, page_id: &str) {
+        let mut sections = match self.page_mut(page_id) {
+            Some(page) => page.help(),
+            None => return,
+        };
+        sections.push(HelpSection::new("Global", vec![
+            (String::from("Help"), String::from("Show this help")),
+            (String::from("Long Touch"), String::from("Show this help")),
+        ]));
+        let input_manager = self.input_manager.borrow();
+        self.help_overlay = Some(HelpOverlay::new(&page_title(page_id), &sections, |action| input_manager.physical_keys(action)));
+    }
+
+    /// The help overlay takes all input like a dialog, the page below is redrawn once it is closed
+    fn help_update (&mut self, current_page_id: &str) {
+        let keys = self.input_manager.borrow_mut().check_keys();
+        let touches = self.input_manager.borrow_mut().check_touches();
+        let closed = match &mut self.help_overlay {
+            Some(help_overlay) => keys.iter().any(|key| help_overlay.key_input(key, HELP_RECT))
+                || touches.iter().any(|touch| matches!(touch, TouchEvent::Tap { .. })),
+            None => return,
+        };
+        if closed {
+            self.help_overlay = None;
+            self.enter_page_area();
+            if let Some(page) = self.page_mut(current_page_id) {
+                page.page_view_redraw();
+            }
+            self.leave_page_area();
+        } else if let Some(help_overlay) = &self.help_overlay {
+            help_overlay.draw(&mut self.display.borrow_mut(), HELP_RECT);
+        }
+    }
+
+    /// Hands the settings to the display, input and core
+    fn apply_settings (&mut self) {
+        let settings = self.settings.borrow();
+        {
+            let mut display = self.display.borrow_mut();
+            if let Err(e) = display.set_brightness(settings.get_number("brightness") as u32) {
+                if !self.brightness_warned {
+                    warn!("Unable to set brightness: {}", e);
+                    self.brightness_warned = true;
+                }
+            }
+            display.set_theme(if settings.get_text("theme") == "Light" { DisplayTheme::Light } else { DisplayTheme::Dark });
+        }
+        let repeat_delay = Duration::from_millis(settings.get_number("key_repeat_delay") as u64);
+        self.input_manager.borrow_mut().set_key_repeat(Some(repeat_delay).filter(|_| settings.get_bool("key_repeat")));
+        let commands = [
+            CoreCommand::SetTrackLength(settings.get_number("track_length") as usize),
+            CoreCommand::SetSampleRate(settings.get_number("sample_rate") as f32),
+        ];
+        for command in commands {
+            let result = self.core.borrow_mut().execute(command);
+            if let Err(e) = result {
+                self.notifier.borrow_mut().error(&e);
             }
-            self.page_index_prvious = current_page_index;
         }
+    }
 
-        match current_page_index {
-            0 => {
-                self.page_0.page_view_update();
+    /// Runs `core_loop` at the core sample rate, independent of the UI frame rate
+    fn core_update (&mut self) {
+        if self.input_manager.borrow().is_replaying() {
+            // a fixed number of ticks per frame, so the replayed input meets the same model time on every run
+            let mut core = self.core.borrow_mut();
+            self.replay_ticks += self.fps.as_secs_f32() * core.sample_rate();
+            while self.replay_ticks >= 1.0 {
+                self.replay_ticks -= 1.0;
+                core.core_loop();
+            }
+            return;
+        }
+        let frame_interval = self.frame_interval();
+        let mut core = self.core.borrow_mut();
+        let core_interval = Duration::from_secs_f32(1.0 / core.sample_rate());
+        // the ticks of two frames, so a slow frame rate does not slow the core down
+        let max_ticks = ((frame_interval.as_secs_f32() * core.sample_rate()).ceil() as usize).max(1) * 2;
+        let mut ticks = 0;
+        while self.core_last_tick.elapsed() >= core_interval {
+            self.core_last_tick += core_interval;
+            core.core_loop();
+            ticks += 1;
+            // do not try to catch up after a long stall
+            if ticks >= max_ticks {
+                self.core_last_tick = Instant::now();
+                break;
+            }
+        }
+    }
+
+    /// Applies the navigation requested by the pages during the previous frame
+    fn apply_navigation (&mut self) {
+        let request = self.navigator.borrow_mut().request.take();
+        let request = match request {
+            Some(request) => request,
+            None => return,
+        };
+        let stack = self.navigator.borrow().stack.clone();
+        let current_page_id = stack.last().cloned();
+
+        if let NavigationRequest::Push(page_id) | NavigationRequest::Switch(page_id) = &request {
+            if !self.pages.iter().any(|(id, _)| id == page_id) {
+                warn!("Unknown page: {}", page_id);
+                return;
+            }
+            if current_page_id.as_ref() == Some(page_id) {
+                return;
+            }
+        }
+
+        match request {
+            NavigationRequest::Push(page_id) => {
+                if let Some(page) = current_page_id.and_then(|id| self.page_mut(&id)) {
+                    page.page_suspend();
+                }
+                self.navigator.borrow_mut().stack.push(page_id);
+            },
+            NavigationRequest::Pop => {
+                if stack.len() < 2 {
+                    return;
+                }
+                if let Some(page) = current_page_id.and_then(|id| self.page_mut(&id)) {
+                    page.page_leave();
+                }
+                self.navigator.borrow_mut().stack.pop();
             },
-            1 => {
-                self.page_1.page_view_update();
+            NavigationRequest::Switch(page_id) => {
+                for id in stack.iter().rev() {
+                    if let Some(page) = self.page_mut(id) {
+                        page.page_leave();
+                    }
+                }
+                self.navigator.borrow_mut().stack = vec![page_id];
             },
-            _ => {todo!()},
         }
+
+        let new_page_id = self.navigator.borrow().current_page().map(|id| id.to_string());
+        if let Some(page) = new_page_id.and_then(|id| self.page_mut(&id)) {
+            page.page_enter();
+        }
+        debug!("Page stack: {:?}", self.navigator.borrow().stack);
     }
 
     pub fn frame_end (&mut self) {
+        let render_time = self.loop_start_time.elapsed();
+        let flush_start = Instant::now();
         self.display.borrow_mut().frame_update();
-        if let Some(remaining) = self.fps.checked_sub(self.loop_start_time.elapsed()) {
-            // info!("{:?}", remaining);
+        let flush_time = flush_start.elapsed();
+        let frame_time = render_time + flush_time;
+
+        self.frame_stats.record(render_time, flush_time, self.frame_interval());
+        if let Some(status_bar) = &mut self.status_bar {
+            status_bar.update_frame_time(frame_time);
+        }
+        if let Some(pacer) = &mut self.frame_pacer {
+            pacer.update(frame_time, self.frame_changed);
+        }
+        if let Some(remaining) = self.frame_interval().checked_sub(self.loop_start_time.elapsed()) {
             thread::sleep(remaining);
         } else {
-            // debug!("Rendering Over Time")
+            debug!("Rendering over time: {:?}", frame_time);
         }
     }
+
+    pub fn is_input_finished (&self) -> bool {
+        self.input_manager.borrow().is_input_finished()
+    }
+}
+
+pub enum NavigationRequest {
+    Push(String),
+    Pop,
+    Switch(String),
+}
+
+/// # Page Navigator
+/// Shared with the pages to request page changes, the `ViewContainer` applies
+/// the request at the start of the next frame and calls the lifecycle hooks.
+pub struct PageNavigator {
+    stack: Vec<String>,
+    request: Option<NavigationRequest>,
+}
+
+impl PageNavigator {
+    fn new() -> Self {
+        PageNavigator {
+            stack: Vec::new(),
+            request: None,
+        }
+    }
+
+    /// Opens a page on top of the current one, `pop` goes back to it
+    pub fn push(&mut self, page_id: &str) {
+        self.request = Some(NavigationRequest::Push(page_id.to_string()));
+    }
+
+    /// Goes back to the previous page, the start page is never popped
+    pub fn pop(&mut self) {
+        self.request = Some(NavigationRequest::Pop);
+    }
+
+    /// Replaces the whole navigation stack with the given page
+    pub fn switch_to(&mut self, page_id: &str) {
+        self.request = Some(NavigationRequest::Switch(page_id.to_string()));
+    }
+
+    pub fn current_page(&self) -> Option<&str> {
+        self.stack.last().map(|id| id.as_str())
+    }
 }
 
 pub trait PageInterface {
     fn page_view_init(&mut self);
-    fn page_view_back(&mut self);
+    /// Page becomes visible, either newly opened or back on top of the stack
+    fn page_enter(&mut self);
+    /// Page is removed from the navigation stack
+    fn page_leave(&mut self) {}
+    /// Another page is pushed on top of this one
+    fn page_suspend(&mut self) {}
     fn process_key_input(&mut self);
     fn process_key_input_block_menu(&mut self);
+    fn process_touch_input(&mut self);
     fn navigate_vertical(&mut self, dir: isize);
     fn navigate_horizontal(&mut self, dir: isize);
     fn call_block_menu(&mut self);
     fn call_page(&mut self);
-    fn update_page_index(&mut self, index: usize);
     fn page_view_update(&mut self);
+    /// Bindings active on the page and inside its focused block, for the help overlay
+    fn help(&self) -> Vec<HelpSection>;
+    /// Redraws the page after a dialog or toast covered it, keeps open menus
+    fn page_view_redraw(&mut self) {
+        self.call_page();
+    }
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>>;
+    /// Run by `PageCommand::OpenMenu`
+    fn open_menu(&mut self) {}
+    /// Runs the shared command bound to `key`, returns the page specific action for the page to execute
+    fn dispatch_key<A: Clone>(&mut self, bindings: &[KeyBinding<PageCommand<A>>], key: &str) -> Option<A> where Self: Sized {
+        match find_command(bindings, key)? {
+            PageCommand::Vertical(dir) => self.navigate_vertical(dir),
+            PageCommand::Horizontal(dir) => self.navigate_horizontal(dir),
+            PageCommand::OpenMenu => self.open_menu(),
+            PageCommand::SwitchTo(page_id) => self.navigator().borrow_mut().switch_to(page_id),
+            PageCommand::Push(page_id) => self.navigator().borrow_mut().push(page_id),
+            PageCommand::Pop => self.navigator().borrow_mut().pop(),
+            PageCommand::Page(action) => return Some(action),
+        }
+        None
+    }
 }
 
 /// # Page 0
@@ -108,47 +557,177 @@ pub struct Page0DataLoader {
     track_number:usize,
     data_loader_blocks: Vec<Box<dyn UiBlockInterface>>,
     wave_preview_blocks: Vec<Box<dyn UiBlockInterface>>,
-    block_coordinates: Vec<Vec<[usize;2]>>,
+    block_rects: Vec<Vec<[usize; 4]>>,
     focus_rect:[usize; 2],
-    key_manager: Rc<RefCell<KeyManager>>,
+    input_manager: Rc<RefCell<InputManager>>,
     block_menu_called: bool,
-    page_index_ref: Rc<RefCell<usize>>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    notifier: Rc<RefCell<Notifier>>,
+    file_browser_request: Rc<RefCell<Option<FileBrowserRequest>>>,
 }
 
 impl Page0DataLoader {
-    fn new(track_number: usize, display_ref: Rc<RefCell<Display>>, key_manager: Rc<RefCell<KeyManager>>, page_index_ref:Rc<RefCell<usize>>) -> Self {
+    fn new(track_number: usize, display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>,
+           core: Rc<RefCell<Core>>, notifier: Rc<RefCell<Notifier>>, file_browser_request: Rc<RefCell<Option<FileBrowserRequest>>>) -> Self {
         let display_ui_block_ref = display_ref.clone();
-        let mut block_coordinates = Vec::new();
-        let mut data_loader_block_coordinates = Vec::new();
-        let gap_height = 480 / track_number;
-        for i in 0..track_number {
-            data_loader_block_coordinates.push([0, gap_height * i]);
-        }
-        let mut wave_preview_block_coordinates = Vec::new();
-        for i in 0..track_number {
-            wave_preview_block_coordinates.push([160, gap_height * i]);
-        }
-        block_coordinates.push(data_loader_block_coordinates);
-        block_coordinates.push(wave_preview_block_coordinates);
-
-        // debug!("{:?}", block_coordinates);
+        let page_size = display_ref.borrow().viewport_size();
+        let block_rects = Self::layout_block_rects(track_number, page_size);
 
         Page0DataLoader {
             display_ref,
             track_number,
-            data_loader_blocks: (0..track_number).map(|i| Box::new(EmptyLoaderUiBlock::new(display_ui_block_ref.clone(), block_coordinates[0][i])) as Box<dyn UiBlockInterface>).collect(),
-            wave_preview_blocks: (0..track_number).map(|i| Box::new(WavePreviewUiBlock::new(display_ui_block_ref.clone(), block_coordinates[1][i])) as Box<dyn UiBlockInterface>).collect(),
-            block_coordinates,
+            data_loader_blocks: (0..track_number).map(|i| Box::new(EmptyLoaderUiBlock::new(display_ui_block_ref.clone(), block_rects[0][i])) as Box<dyn UiBlockInterface>).collect(),
+            wave_preview_blocks: (0..track_number).map(|i| Box::new(WavePreviewUiBlock::new(display_ui_block_ref.clone(), block_rects[1][i], i)) as Box<dyn UiBlockInterface>).collect(),
+            block_rects,
             focus_rect: [0, 0],
-            key_manager,
+            input_manager,
             block_menu_called: false,
-            page_index_ref,
+            navigator,
+            core,
+            notifier,
+            file_browser_request,
+        }
+    }
+
+    /// One row per track, the loader block on the left and the wave preview on the right
+    fn layout(track_number: usize) -> Layout {
+        Layout::column((0..track_number).map(|i| Layout::row(vec![
+            Layout::slot(&format!("loader_{}", i)),
+            Layout::slot(&format!("preview_{}", i)).weight(3),
+        ]).spacing(20)).collect()).padding(10).spacing(20)
+    }
+
+    /// Block rectangles indexed like `focus_rect`, column first
+    fn layout_block_rects(track_number: usize, page_size: [usize; 2]) -> Vec<Vec<[usize; 4]>> {
+        let rects = Self::layout(track_number).compute([0, 0, page_size[0], page_size[1]]);
+        ["loader", "preview"].iter().map(|column| {
+            (0..track_number).map(|i| rects.get(&format!("{}_{}", column, i)).unwrap_or([0, 0, 0, 0])).collect()
+        }).collect()
+    }
+
+    /// Lays the blocks out again when the page area changed, e.g. by the status bar
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let block_rects = Self::layout_block_rects(self.track_number, page_size);
+        if block_rects == self.block_rects {
+            return;
         }
+        for i in 0..self.track_number {
+            self.data_loader_blocks[i].set_rect(block_rects[0][i]);
+            self.wave_preview_blocks[i].set_rect(block_rects[1][i]);
+        }
+        self.block_rects = block_rects;
     }
 
     pub fn select_data_loader(&mut self, index: usize, data_loader: Box<dyn UiBlockInterface>) {
         self.data_loader_blocks[index] = data_loader;
     }
+
+    fn execute_core_command(&mut self, command: CoreCommand) {
+        let result = self.core.borrow_mut().execute(command);
+        if let Err(e) = result {
+            self.notifier.borrow_mut().error(&e);
+        }
+    }
+
+    /// Creates the loader in the core and swaps in the UI block of that loader type
+    fn create_data_loader(&mut self, track_index: usize, action: BlockMenuAction) {
+        let rect = self.block_rects[0][track_index];
+        let display_ref = self.display_ref.clone();
+        let block: Box<dyn UiBlockInterface> = match action {
+            BlockMenuAction::CreateWaveGenerator(wave_generate_type) => {
+                self.execute_core_command(CoreCommand::SetTrackLoader {
+                    track_index,
+                    loader_type: String::from("wave_generator"),
+                    wave_generate_type: wave_generate_type.clone(),
+                });
+                Box::new(WaveGeneratorUiBlock::new(display_ref, rect, wave_generate_type, track_index))
+            },
+            BlockMenuAction::LoadWaveFromFile => {
+                // the wave generate type is ignored for file loaders
+                self.execute_core_command(CoreCommand::SetTrackLoader {
+                    track_index,
+                    loader_type: String::from("file_loader"),
+                    wave_generate_type: WaveGenerateType::Sine,
+                });
+                self.open_file_browser(FileBrowserRequest::Load { track_index });
+                Box::new(FileLoaderUiBlock::new(display_ref, rect, track_index))
+            },
+            BlockMenuAction::RemoveLoader => {
+                self.execute_core_command(CoreCommand::SetTrackLoader {
+                    track_index,
+                    loader_type: String::from("none"),
+                    wave_generate_type: WaveGenerateType::Sine,
+                });
+                Box::new(EmptyLoaderUiBlock::new(display_ref, rect))
+            },
+            _ => return,
+        };
+        self.select_data_loader(track_index, block);
+        self.data_loader_blocks[track_index].set_selected(self.focus_rect == [0, track_index]);
+    }
+
+    fn open_file_browser(&mut self, request: FileBrowserRequest) {
+        *self.file_browser_request.borrow_mut() = Some(request);
+        self.navigator.borrow_mut().push("file_browser");
+    }
+
+    fn handle_block_menu_result(&mut self, result: MenuResult<BlockMenuAction>) {
+        match result {
+            MenuResult::Selected(action) => {
+                debug!("Menu Executed: {:?}", action);
+                match action {
+                    BlockMenuAction::OpenWaveEditor => {
+                        self.execute_core_command(CoreCommand::SelectTrack(self.focus_rect[1]));
+                        self.navigator.borrow_mut().switch_to("wave_editor");
+                    },
+                    BlockMenuAction::EditGenerator => {
+                        self.execute_core_command(CoreCommand::SelectTrack(self.focus_rect[1]));
+                        self.navigator.borrow_mut().push("generator_editor");
+                    },
+                    BlockMenuAction::BrowseFile => self.open_file_browser(FileBrowserRequest::Load { track_index: self.focus_rect[1] }),
+                    BlockMenuAction::SaveToFile => self.open_file_browser(FileBrowserRequest::Save { track_index: self.focus_rect[1] }),
+                    BlockMenuAction::RenameTrack => {
+                        let name = self.core.borrow().wave_container.get_track_name(self.focus_rect[1]).to_string();
+                        self.notifier.borrow_mut().open_text_input(TRACK_NAME_INPUT_ID, "Track Name", &name, TRACK_NAME_MAX_LENGTH);
+                    },
+                    action => self.create_data_loader(self.focus_rect[1], action),
+                }
+                self.call_page();
+            },
+            MenuResult::Closed => self.call_page(),
+            MenuResult::None => {},
+        }
+    }
+
+    /// Keys of the page, dispatched by `process_key_input` and listed by the help overlay
+    fn key_bindings() -> Vec<KeyBinding<PageCommand<CoreCommand>>> {
+        vec![
+            KeyBinding::new("Up", "Previous track", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next track", PageCommand::Vertical(1)),
+            KeyBinding::new("Left", "Loader column", PageCommand::Horizontal(-1)),
+            KeyBinding::new("Right", "Preview column", PageCommand::Horizontal(1)),
+            KeyBinding::new("Menu", "Block menu", PageCommand::OpenMenu),
+            KeyBinding::new("Play", "Play / stop", PageCommand::Page(CoreCommand::TogglePlay)),
+            KeyBinding::new("Page2", "Wave editor", PageCommand::SwitchTo("wave_editor")),
+            KeyBinding::new("Page3", "Operator rack", PageCommand::SwitchTo("operator_rack")),
+            KeyBinding::new("Page4", "Settings", PageCommand::Push("settings")),
+            KeyBinding::new("Overview", "Track overview", PageCommand::SwitchTo("track_overview")),
+            KeyBinding::new("Back", "Back", PageCommand::Pop),
+        ]
+    }
+
+    /// Renames the focused track once the track name input was confirmed
+    fn process_track_name_input(&mut self) {
+        let result = self.notifier.borrow_mut().take_text_input_result(TRACK_NAME_INPUT_ID);
+        if let Some(TextInputResult::Confirmed(name)) = result {
+            let name = name.trim();
+            if !name.is_empty() {
+                self.execute_core_command(CoreCommand::RenameTrack { track_index: self.focus_rect[1], name: name.to_string() });
+            }
+        }
+    }
 }
 
 
@@ -161,7 +740,8 @@ impl PageInterface for Page0DataLoader {
         }
     }
 
-    fn page_view_back(&mut self) {
+    fn page_enter(&mut self) {
+        self.apply_layout();
         self.display_ref.borrow_mut().clean();
         for i in 0..self.track_number {
             self.data_loader_blocks[i].block_view_update();
@@ -169,48 +749,52 @@ impl PageInterface for Page0DataLoader {
         }
     }
 
+    fn page_view_redraw(&mut self) {
+        self.page_enter();
+    }
+
     fn process_key_input(&mut self) {
-        let key = self.key_manager.borrow_mut().check_keys();
+        let key = self.input_manager.borrow_mut().check_keys();
         if let Some(first_key) = key.get(0) {
             debug!("{:?}", first_key);
-            match first_key.as_str() {
-                "Up" => self.navigate_vertical(-1),
-                "Down" => self.navigate_vertical(1),
-                "Left" => self.navigate_horizontal(-1),
-                "Right" => self.navigate_horizontal(1),
-                "2" => self.update_page_index(1),
-                "M" => self.block_menu_called = true,
-                _ => {},
+            if let Some(command) = self.dispatch_key(&Self::key_bindings(), first_key) {
+                self.execute_core_command(command);
             }
         }
     }
 
     fn process_key_input_block_menu(&mut self) {
-        let key = self.key_manager.borrow_mut().check_keys();
+        let key = self.input_manager.borrow_mut().check_keys();
         if let Some(first_key) = key.get(0) {
-            match first_key.as_str() {
-                "Up" => {
-                    if self.focus_rect[0] == 0 {
-                        self.data_loader_blocks[self.focus_rect[1]].block_key_input("Up");
-                    }
-                },
-                "Down" => {
-                    if self.focus_rect[0] == 0 {
-                        self.data_loader_blocks[self.focus_rect[1]].block_key_input("Down");
-                    }
-                },
-                "Left" =>  {
-                    if self.focus_rect[0] == 0 {
-                        self.data_loader_blocks[self.focus_rect[1]].block_key_input("Left");
-                    }
-                },
-                "Right" => {
-                    if self.focus_rect[0] == 0 {
-                        self.data_loader_blocks[self.focus_rect[1]].block_key_input("Right");
+            let result = match self.focus_rect[0] {
+                0 => self.data_loader_blocks[self.focus_rect[1]].block_key_input(first_key),
+                _ => self.wave_preview_blocks[self.focus_rect[1]].block_key_input(first_key),
+            };
+            self.handle_block_menu_result(result);
+        }
+    }
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if let TouchEvent::Tap { x, y } = touch {
+                if self.block_menu_called {
+                    let result = match self.focus_rect[0] {
+                        0 => self.data_loader_blocks[self.focus_rect[1]].block_touch_input(x, y),
+                        _ => self.wave_preview_blocks[self.focus_rect[1]].block_touch_input(x, y),
+                    };
+                    self.handle_block_menu_result(result);
+                    continue;
+                }
+                let hit = self.data_loader_blocks.iter().position(|block| rect_contains(block.block_rect(), x, y)).map(|i| [0, i])
+                    .or_else(|| self.wave_preview_blocks.iter().position(|block| rect_contains(block.block_rect(), x, y)).map(|i| [1, i]));
+                if let Some(block_index) = hit {
+                    if block_index == self.focus_rect {
+                        self.block_menu_called = true;
+                    } else {
+                        self.focus_rect = block_index;
                     }
-                },
-                "M" => self.call_page(),
-                _ => {},
+                }
             }
         }
     }
@@ -231,6 +815,10 @@ impl PageInterface for Page0DataLoader {
         }
     }
     
+    fn open_menu(&mut self) {
+        self.block_menu_called = true;
+    }
+
     fn call_block_menu(&mut self) {
         match self.focus_rect[0] {
             0 => self.data_loader_blocks[self.focus_rect[1]].call_menu(),
@@ -248,19 +836,35 @@ impl PageInterface for Page0DataLoader {
         self.block_menu_called = false
     }
 
-    fn update_page_index(&mut self, index:usize){
-        *self.page_index_ref.borrow_mut() = index;
+    fn help(&self) -> Vec<HelpSection> {
+        let focused_block = match self.focus_rect[0] {
+            0 => &self.data_loader_blocks[self.focus_rect[1]],
+            _ => &self.wave_preview_blocks[self.focus_rect[1]],
+        };
+        let page_section = if self.block_menu_called {
+            menu_key_help()
+        } else {
+            HelpSection::from_bindings("Page", &Self::key_bindings())
+        };
+        vec![page_section, focused_block.block_help()]
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
     }
 
     fn page_view_update(&mut self) {
 
+        self.process_track_name_input();
         if self.block_menu_called {
             self.call_block_menu();
             self.process_key_input_block_menu();
+            self.process_touch_input();
 
         } else {
             let selected_block_index = self.focus_rect;
             self.process_key_input();
+            self.process_touch_input();
             // debug!("{:?}", self.focus_rect);
 
             // block selection
@@ -280,195 +884,410 @@ impl PageInterface for Page0DataLoader {
                     self.wave_preview_blocks[i].block_view_update();
                 }
             }
+
+            if self.block_menu_called {
+                return;
+            }
+            let core = self.core.borrow();
+            for block in self.data_loader_blocks.iter_mut().chain(self.wave_preview_blocks.iter_mut()) {
+                if block.block_core_update(&core) {
+                    block.block_view_update();
+                }
+            }
         }
     }
 }
 
 pub trait UiBlockInterface {
+    /// Shared state the default methods work on
+    fn base(&self) -> &UiBlockBase;
+    fn base_mut(&mut self) -> &mut UiBlockBase;
     fn block_view_update(&mut self);
-    fn call_menu(&mut self);
-    fn get_block_name(&self) -> String;
-    fn set_selected(&mut self, is_selected: bool);
-    fn block_key_input(&mut self, key: &str);
+
+    fn call_menu(&mut self) {
+        let base = self.base_mut();
+        let mut display = base.display_ref.borrow_mut();
+        base.menu.draw(&mut display, MENU_RECT);
+    }
+
+    fn get_block_name(&self) -> String {
+        self.base().name.clone()
+    }
+
+    fn set_selected(&mut self, is_selected: bool) {
+        self.base_mut().is_selected = is_selected;
+    }
+
+    fn block_key_input(&mut self, key: &str) -> MenuResult<BlockMenuAction> {
+        let base = self.base_mut();
+        let result = base.menu.key_input(key);
+        base.menu_result(result)
+    }
+
+    fn block_touch_input(&mut self, x: usize, y: usize) -> MenuResult<BlockMenuAction> {
+        let base = self.base_mut();
+        let result = base.menu.touch_input(MENU_RECT, x, y);
+        base.menu_result(result)
+    }
+
+    fn block_rect(&self) -> [usize; 4] {
+        self.base().rect
+    }
+
+    /// Moves the block to the rectangle assigned by the page layout
+    fn set_rect(&mut self, rect: [usize; 4]) {
+        self.base_mut().rect = rect;
+    }
+
+    /// Items of the block menu, for the help overlay
+    fn block_help(&self) -> HelpSection {
+        self.base().menu.help_section(&format!("Block: {}", self.get_block_name()))
+    }
+
+    /// Reads the core state, returns true when the block has to be redrawn
+    fn block_core_update(&mut self, _core: &Core) -> bool {
+        false
+    }
 }
 
-/// # UI Block: Empty_Data_Loader
-struct EmptyLoaderUiBlock {
+/// Actions offered by the block menus
+#[derive(Clone, Debug)]
+pub enum BlockMenuAction {
+    LoadWaveFromFile,
+    CreateWaveGenerator(WaveGenerateType),
+    CreateSensorReader,
+    RemoveLoader,
+    OpenWaveEditor,
+    EditGenerator,
+    BrowseFile,
+    SaveToFile,
+    RenameTrack,
+}
+
+pub(crate) fn wave_generate_type_name(wave_generate_type: &WaveGenerateType) -> &'static str {
+    match wave_generate_type {
+        WaveGenerateType::Rectangular(_) => "Rectangular",
+        WaveGenerateType::Sine => "Sine",
+        WaveGenerateType::Triangle => "Triangle",
+        WaveGenerateType::Sawtooth => "Sawtooth",
+        WaveGenerateType::Noise => "Noise",
+    }
+}
+
+/// Short loader status shown next to the track name
+pub(crate) fn track_loader_label(track_loader: &TrackLoader) -> String {
+    match track_loader {
+        TrackLoader::FileLoader(_) => String::from("File"),
+        TrackLoader::SensorReader(_) => String::from("Sensor"),
+        TrackLoader::WaveGenerator(generator) => format!("Gen {}", wave_generate_type_name(generator.get_wave_type())),
+        TrackLoader::None => String::from("Empty"),
+    }
+}
+
+/// Screen area of the block menus
+pub(crate) const MENU_RECT: [usize; 4] = [50, 50, 400, 400];
+/// Screen area of the text input keyboard
+const TEXT_INPUT_RECT: [usize; 4] = [20, 100, 460, 420];
+/// Screen area of the confirm dialogs
+const DIALOG_RECT: [usize; 4] = [60, 160, 420, 320];
+/// Screen area of the frame statistics overlay
+const FRAME_STATS_RECT: [usize; 4] = [250, 370, 470, 428];
+/// Screen area of the help overlay
+const HELP_RECT: [usize; 4] = [20, 30, 460, 430];
+/// Screen area of the toast notifications
+const TOAST_RECT: [usize; 4] = [10, 440, 470, 470];
+/// Track names have to fit into the wave editor tabs
+const TRACK_NAME_MAX_LENGTH: usize = 12;
+const TRACK_NAME_INPUT_ID: &str = "track_name";
+
+pub(crate) fn rect_contains(rect: [usize; 4], x: usize, y: usize) -> bool {
+    x >= rect[0] && x <= rect[2] && y >= rect[1] && y <= rect[3]
+}
+
+/// State shared by the data loader page blocks: position, selection and the block menu
+pub struct UiBlockBase {
     display_ref: Rc<RefCell<Display>>,
-    data_loader_name: String,
+    name: String,
     is_selected: bool,
-    coordinate: [usize; 2],
-    coordinate_shift_x: usize,
-    coordinate_shift_y: usize,
-    block_ui_width: usize,
-    block_ui_height: usize,
-    menu: EmptyBlockMenu,
+    rect: [usize; 4],
+    menu: Menu<BlockMenuAction>,
 }
 
-impl EmptyLoaderUiBlock {
-    fn new(display_ref: Rc<RefCell<Display>>, coordinate:[usize; 2]) -> Self {
-        EmptyLoaderUiBlock {
+impl UiBlockBase {
+    fn new(display_ref: Rc<RefCell<Display>>, name: &str, rect: [usize; 4], menu: Menu<BlockMenuAction>) -> Self {
+        UiBlockBase {
             display_ref,
-            data_loader_name: String::from("Empty"),
+            name: String::from(name),
             is_selected: false,
-            coordinate,
-            coordinate_shift_x: 10,
-            coordinate_shift_y: 10,
-            block_ui_width: 80,
-            block_ui_height: 50,
-            menu: EmptyBlockMenu::new(),
+            rect,
+            menu,
+        }
+    }
+
+    /// Block background, highlighted while selected
+    fn draw_background(&self, display: &mut Display) {
+        let color = if self.is_selected { (100,30,30) } else { (30,30,30) };
+        display.draw_rectangle(self.rect[0], self.rect[1], self.rect[2], self.rect[3], color, true);
+    }
+
+    /// The menu goes back to its top level once it returns a result
+    fn menu_result(&mut self, result: MenuResult<BlockMenuAction>) -> MenuResult<BlockMenuAction> {
+        if !matches!(result, MenuResult::None) {
+            self.menu.reset();
+        }
+        result
+    }
+}
+
+/// # UI Block: Empty_Data_Loader
+struct EmptyLoaderUiBlock {
+    base: UiBlockBase,
+}
+
+impl EmptyLoaderUiBlock {
+    fn new(display_ref: Rc<RefCell<Display>>, rect: [usize; 4]) -> Self {
+        EmptyLoaderUiBlock {
+            base: UiBlockBase::new(display_ref, "Empty", rect, Menu::new("Empty Block Menu", vec![
+                MenuItem::new_action("Load Wave From File", BlockMenuAction::LoadWaveFromFile),
+                MenuItem::new_submenu("Create Wave Generator", Menu::new("Wave Generator", vec![
+                    MenuItem::new_action("Sine", BlockMenuAction::CreateWaveGenerator(WaveGenerateType::Sine)),
+                    MenuItem::new_action("Triangle", BlockMenuAction::CreateWaveGenerator(WaveGenerateType::Triangle)),
+                    MenuItem::new_action("Sawtooth", BlockMenuAction::CreateWaveGenerator(WaveGenerateType::Sawtooth)),
+                    MenuItem::new_action("Rectangular", BlockMenuAction::CreateWaveGenerator(WaveGenerateType::Rectangular(0.5))),
+                    MenuItem::new_action("Noise", BlockMenuAction::CreateWaveGenerator(WaveGenerateType::Noise)),
+                ])),
+                // sensor readers are not supported by the data loader container yet
+                MenuItem::new_action("Create Sensor Reader", BlockMenuAction::CreateSensorReader).disabled(),
+            ])),
         }
     }
 }
 
 impl UiBlockInterface for EmptyLoaderUiBlock {
+    fn base(&self) -> &UiBlockBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut UiBlockBase {
+        &mut self.base
+    }
+
     fn block_view_update(&mut self) {
-        let mut color = (30,30,30);
-        if self.is_selected {
-            color = (100,30,30);
-        }
-        let mut display = self.display_ref.borrow_mut();
-        // debug!("{:?}", self.coordinate);
-        display.draw_rectangle(self.coordinate[0]+self.coordinate_shift_x,
-                               self.coordinate[1]+self.coordinate_shift_y,
-                               self.coordinate[0]+self.block_ui_width+self.coordinate_shift_x,
-                               self.coordinate[1]+self.block_ui_height+self.coordinate_shift_y,
-                               color,
-                               true);
-        display.text(&self.data_loader_name, 1,
-                     self.coordinate[0]+self.coordinate_shift_x+5,
-                     self.coordinate[1]+self.coordinate_shift_y+5,
+        let base = &self.base;
+        let mut display = base.display_ref.borrow_mut();
+        base.draw_background(&mut display);
+        display.text(&base.name, 1,
+                     base.rect[0]+5,
+                     base.rect[1]+5,
                      1, 1, (0, 255, 0));
     }
+}
 
-    fn call_menu(&mut self) {
-        // debug!("Menu Called: {}", self.data_loader_name);
-        let mut display = self.display_ref.borrow_mut();
-        display.draw_rectangle(50,50,400,400,(0,100,0),true);
-        display.text("Empty Block Menu", 2, 70, 60, 2, 8, (255,255,255));
-        for (index, item) in self.menu.items.iter().enumerate() {
-            let color = if self.menu.selected_index == index {
-                (255, 0, 0)
-            } else {
-                (255, 255, 255)
-            };
-            display.text(item, 1, 70, 100 + index * 20, 1, 1, color);
+/// # UI Block: Wave Generator Loader
+struct WaveGeneratorUiBlock {
+    base: UiBlockBase,
+    wave_generate_type: WaveGenerateType,
+    track_index: usize,
+}
+
+impl WaveGeneratorUiBlock {
+    fn new(display_ref: Rc<RefCell<Display>>, rect: [usize; 4], wave_generate_type: WaveGenerateType, track_index: usize) -> Self {
+        WaveGeneratorUiBlock {
+            base: UiBlockBase::new(display_ref, "Wave Gen", rect, Menu::new("Wave Generator", vec![
+                MenuItem::new_action("Edit Parameters", BlockMenuAction::EditGenerator),
+                MenuItem::new_action("Remove Loader", BlockMenuAction::RemoveLoader),
+            ])),
+            wave_generate_type,
+            track_index,
         }
     }
+}
 
-    fn get_block_name(&self) -> String {
-        self.data_loader_name.clone()
+impl UiBlockInterface for WaveGeneratorUiBlock {
+    fn base(&self) -> &UiBlockBase {
+        &self.base
     }
 
-    fn set_selected(&mut self, is_selected: bool) {
-        self.is_selected = is_selected;
+    fn base_mut(&mut self) -> &mut UiBlockBase {
+        &mut self.base
     }
 
-    fn block_key_input(&mut self, key: &str) {
-        match key {
-            "Up" => {
-                if self.menu.selected_index > 0 {
-                    self.menu.selected_index -= 1;
-                }
-            },
-            "Down" => {
-                if self.menu.selected_index < self.menu.items.len() - 1 {
-                    self.menu.selected_index += 1;
-                }
-            },
-            "Left" => debug!("Left"),
-            "Right" => {
-                self.menu.execute();
-            },
-            _ => {},
+    fn block_view_update(&mut self) {
+        let base = &self.base;
+        let mut display = base.display_ref.borrow_mut();
+        base.draw_background(&mut display);
+        display.text(&base.name, 1,
+                     base.rect[0]+5,
+                     base.rect[1]+5,
+                     1, 1, (0, 255, 0));
+        display.text(wave_generate_type_name(&self.wave_generate_type), 1,
+                     base.rect[0]+5,
+                     base.rect[1]+25,
+                     1, 1, (200, 200, 200));
+    }
+
+    fn block_core_update(&mut self, core: &Core) -> bool {
+        if let TrackLoader::WaveGenerator(generator) = &core.data_loader_container.track_loader[self.track_index] {
+            if wave_generate_type_name(generator.get_wave_type()) != wave_generate_type_name(&self.wave_generate_type) {
+                self.wave_generate_type = generator.get_wave_type().clone();
+                return true;
+            }
         }
+        false
     }
 }
 
-struct EmptyBlockMenu {
-    items: Vec<String>,
-    pub selected_index: usize,
+/// # UI Block: File Loader
+struct FileLoaderUiBlock {
+    base: UiBlockBase,
+    file_name: Option<String>,
+    track_index: usize,
 }
 
-impl EmptyBlockMenu {
-    fn new() -> Self {
-        EmptyBlockMenu {
-            items: vec![String::from("Load Wave From File"), String::from("Create Wave Generator"), String::from("Create Sensor Reader")],
-            selected_index: 0,
+impl FileLoaderUiBlock {
+    fn new(display_ref: Rc<RefCell<Display>>, rect: [usize; 4], track_index: usize) -> Self {
+        FileLoaderUiBlock {
+            base: UiBlockBase::new(display_ref, "File", rect, Menu::new("File Loader", vec![
+                MenuItem::new_action("Load File", BlockMenuAction::BrowseFile),
+                MenuItem::new_action("Remove Loader", BlockMenuAction::RemoveLoader),
+            ])),
+            file_name: None,
+            track_index,
         }
     }
+}
+
+impl UiBlockInterface for FileLoaderUiBlock {
+    fn base(&self) -> &UiBlockBase {
+        &self.base
+    }
 
-    fn execute(&self) {
-        debug!("Menu Executed: {}", self.items[self.selected_index]);
+    fn base_mut(&mut self) -> &mut UiBlockBase {
+        &mut self.base
+    }
+
+    fn block_view_update(&mut self) {
+        let base = &self.base;
+        let mut display = base.display_ref.borrow_mut();
+        base.draw_background(&mut display);
+        display.text(&base.name, 1,
+                     base.rect[0]+5,
+                     base.rect[1]+5,
+                     1, 1, (0, 255, 0));
+        display.text(self.file_name.as_deref().unwrap_or("No File"), 1,
+                     base.rect[0]+5,
+                     base.rect[1]+25,
+                     1, 1, (200, 200, 200));
+    }
+
+    fn block_core_update(&mut self, core: &Core) -> bool {
+        let rect = self.block_rect();
+        let file_name = match &core.data_loader_container.track_loader[self.track_index] {
+            TrackLoader::FileLoader(loader) => loader.get_file_path()
+                .map(|path| Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| String::from(path)))
+                // about 6 pixels per character, 80 pixels fit 13
+                .map(|name| shorten_end(&name, (rect[2] - rect[0]) / 6)),
+            _ => None,
+        };
+        let changed = file_name != self.file_name;
+        self.file_name = file_name;
+        changed
     }
 }
 
 
 /// # UI Block: Wave Preview
+/// Mini waveform of the linked track with the playback pointer, the value under the pointer and the loader type
 struct WavePreviewUiBlock {
-    display_ref: Rc<RefCell<Display>>,
-    wave_preview_name: String,
-    is_selected: bool,
-    coordinate: [usize; 2],
-    coordinate_shift_x: usize,
-    coordinate_shift_y: usize,
-    block_ui_width: usize,
-    block_ui_height: usize,
+    base: UiBlockBase,
+    track_index: usize,
+    label: String,
+    wave_points: Vec<usize>,
+    pointer_x: Option<usize>,
+    current_value: Option<i32>,
 }
 
 impl WavePreviewUiBlock {
-    pub fn new (display_ref:Rc<RefCell<Display>>, coordinate:[usize;2]) -> Self {
+    pub fn new (display_ref:Rc<RefCell<Display>>, rect: [usize; 4], track_index: usize) -> Self {
         WavePreviewUiBlock{
-            display_ref,
-            wave_preview_name: String::from("WavePreview"),
-            is_selected: false,
-            coordinate,
-            coordinate_shift_x: 10,
-            coordinate_shift_y: 10,
-            block_ui_width: 80,
-            block_ui_height: 50,
+            base: UiBlockBase::new(display_ref, "WavePreview", rect, Menu::new("Wave Preview Menu", vec![
+                MenuItem::new_action("Open In Wave Editor", BlockMenuAction::OpenWaveEditor),
+                MenuItem::new_action("Save Track To File", BlockMenuAction::SaveToFile),
+                MenuItem::new_action("Rename Track", BlockMenuAction::RenameTrack),
+            ])),
+            track_index,
+            label: String::from("Empty"),
+            wave_points: Vec::new(),
+            pointer_x: None,
+            current_value: None,
         }
     }
 }
 
 impl UiBlockInterface for WavePreviewUiBlock {
-    fn block_view_update(&mut self) {
-        let mut color = (30,30,30);
-        if self.is_selected {
-            color = (100,30,30);
-        }
-        let mut display = self.display_ref.borrow_mut();
-        // debug!("{:?}", self.coordinate);
-        display.draw_rectangle(self.coordinate[0]+self.coordinate_shift_x,
-                               self.coordinate[1]+self.coordinate_shift_y,
-                               self.coordinate[0]+self.block_ui_width+self.coordinate_shift_x,
-                               self.coordinate[1]+self.block_ui_height+self.coordinate_shift_y,
-                               color,
-                               true);
-        display.text(&self.wave_preview_name, 1,
-                     self.coordinate[0]+self.coordinate_shift_x+5,
-                     self.coordinate[1]+self.coordinate_shift_y+5,
-                     1, 1, (0, 255, 0));
+    fn base(&self) -> &UiBlockBase {
+        &self.base
     }
 
-    fn call_menu(&mut self) {
-        todo!()
+    fn base_mut(&mut self) -> &mut UiBlockBase {
+        &mut self.base
     }
 
-    fn get_block_name(&self) -> String {
-        self.wave_preview_name.clone()
-    }
+    fn block_view_update(&mut self) {
+        let [x0, y0, x1, y1] = self.block_rect();
+        let mut display = self.base.display_ref.borrow_mut();
+        self.base.draw_background(&mut display);
 
-    fn set_selected(&mut self, is_selected: bool) {
-        self.is_selected = is_selected;
+        // waveform, one point per column
+        for (i, pair) in self.wave_points.windows(2).enumerate() {
+            display.draw_line(x0 + i, y0 + pair[0], x0 + i + 1, y0 + pair[1], (0, 200, 255));
+        }
+        if let Some(pointer_x) = self.pointer_x {
+            display.draw_line(x0 + pointer_x, y0, x0 + pointer_x, y1, (255, 255, 0));
+        }
+
+        display.text(&self.label, 1, x0 + 5, y0 + 2, 1, 1, (0, 255, 0));
+        if let Some(value) = self.current_value {
+            let value_text = value.to_string();
+            let value_width = display.text_width(&value_text, 2, 1, 1);
+            display.text(&value_text, 2, x1 - 5 - value_width, y1 - 22, 1, 1, (255, 255, 255));
+        }
     }
 
-    fn block_key_input(&mut self, key: &str) {
-        todo!()
+    fn block_core_update(&mut self, core: &Core) -> bool {
+        let loader_name = track_loader_label(&core.data_loader_container.track_loader[self.track_index]);
+        let label = format!("{}  {}", core.wave_container.get_track_name(self.track_index), loader_name);
+
+        let track = core.wave_container.wave_track[self.track_index].borrow();
+        let [x0, y0, x1, y1] = self.block_rect();
+        let width = x1 - x0;
+        let height = y1 - y0;
+        // nothing to plot without data or without room, e.g. a collapsed layout slot
+        let (wave_points, pointer_x, current_value) = if track.is_empty() || width == 0 {
+            (Vec::new(), None, None)
+        } else {
+            let min = *track.iter().min().unwrap();
+            let max = *track.iter().max().unwrap();
+            let range = (max as i64 - min as i64).max(1);
+            let wave_points = (0..=width).map(|px| {
+                let value = track[px * (track.len() - 1) / width] as i64;
+                // keep a 4 pixel margin at top and bottom, larger values are drawn higher
+                4 + ((max as i64 - value) * (height as i64 - 8) / range) as usize
+            }).collect();
+            let time = core.time() % track.len();
+            (wave_points, Some(time * width / track.len()), Some(track[time]))
+        };
+
+        let changed = label != self.label || wave_points != self.wave_points
+            || pointer_x != self.pointer_x || current_value != self.current_value;
+        self.label = label;
+        self.wave_points = wave_points;
+        self.pointer_x = pointer_x;
+        self.current_value = current_value;
+        changed
     }
 }
 
-
 pub trait UiWaveEditorInterface {
     fn block_view_update(&mut self);
     fn call_menu(&mut self);
@@ -477,96 +1296,306 @@ pub trait UiWaveEditorInterface {
     fn block_key_input(&mut self, key: &str);
 }
 
+/// Actions of the wave editor menu
+#[derive(Clone, Debug)]
+enum WaveEditorAction {
+    Core(CoreCommand),
+    ZoomIn,
+    ZoomOut,
+    ScaleUp,
+    ScaleDown,
+    ToggleRecordArm,
+    TempoUp,
+    TempoDown,
+    ToggleCursors,
+    NextCursor,
+}
+
 /// # Page 1
+/// Editor for the track selected in the `WaveContainer`
 pub struct Page1WaveEditor {
     display_ref: Rc<RefCell<Display>>,
     track_number:usize,
-    wave_edit_blocks: Vec<Box<dyn WaveEditorUiBlockInterface>>,
-    wave_preview_block_coordinates: Vec<[usize;2]>,
-    focus_rect:usize,
-    key_manager: Rc<RefCell<KeyManager>>,
+    wave_edit_block: Box<dyn WaveEditorUiBlockInterface>,
+    tab_rects: Vec<[usize; 4]>,
+    selected_track: usize,
+    input_manager: Rc<RefCell<InputManager>>,
     block_menu_called: bool,
-    page_index_ref: Rc<RefCell<usize>>,
+    menu: Menu<WaveEditorAction>,
+    navigator: Rc<RefCell<PageNavigator>>,
+    core: Rc<RefCell<Core>>,
+    notifier: Rc<RefCell<Notifier>>,
 }
 
 impl Page1WaveEditor {
-    fn new(track_number: usize, display_ref: Rc<RefCell<Display>>, key_manager: Rc<RefCell<KeyManager>>, page_index_ref:Rc<RefCell<usize>>) -> Self {
+    fn new(track_number: usize, display_ref: Rc<RefCell<Display>>, input_manager: Rc<RefCell<InputManager>>, navigator: Rc<RefCell<PageNavigator>>, core: Rc<RefCell<Core>>, notifier: Rc<RefCell<Notifier>>) -> Self {
         let display_ui_block_ref = display_ref.clone();
-        let gap_height = 480 / track_number;
-        let mut wave_preview_block_coordinates = Vec::new();
-        for i in 0..track_number {
-            wave_preview_block_coordinates.push([10, gap_height * i]);
-        }
+        let page_size = display_ref.borrow().viewport_size();
+        let (tab_rects, editor_rect) = Self::layout_rects(track_number, page_size);
 
         Page1WaveEditor{
             display_ref,
             track_number,
-            wave_edit_blocks: (0..track_number).map(|i| Box::new(WaveEditorUiBlock::new(display_ui_block_ref.clone(), wave_preview_block_coordinates[i])) as Box<dyn WaveEditorUiBlockInterface>).collect(),
-            wave_preview_block_coordinates,
-            focus_rect: 0,
-            key_manager,
+            wave_edit_block: Box::new(WaveEditorUiBlock::new(display_ui_block_ref, editor_rect)),
+            tab_rects,
+            selected_track: 0,
+            input_manager,
             block_menu_called: false,
-            page_index_ref,
+            menu: Menu::new("Wave Editor", vec![
+                MenuItem::new_action("Mark In", WaveEditorAction::Core(CoreCommand::MarkIn)),
+                MenuItem::new_action("Mark Out", WaveEditorAction::Core(CoreCommand::MarkOut)),
+                MenuItem::new_action("Cancel In/Out", WaveEditorAction::Core(CoreCommand::CancelInOut)),
+                MenuItem::new_action("Copy", WaveEditorAction::Core(CoreCommand::Copy)),
+                MenuItem::new_action("Paste", WaveEditorAction::Core(CoreCommand::Paste)),
+                MenuItem::new_action("Insert", WaveEditorAction::Core(CoreCommand::Insert)),
+                MenuItem::new_submenu("View", Menu::new("View", vec![
+                    MenuItem::new_action("Zoom In", WaveEditorAction::ZoomIn),
+                    MenuItem::new_action("Zoom Out", WaveEditorAction::ZoomOut),
+                    MenuItem::new_action("Scale Up", WaveEditorAction::ScaleUp),
+                    MenuItem::new_action("Scale Down", WaveEditorAction::ScaleDown),
+                ])),
+                MenuItem::new_submenu("Transport", Menu::new("Transport", vec![
+                    MenuItem::new_action("Play / Stop", WaveEditorAction::Core(CoreCommand::TogglePlay)),
+                    MenuItem::new_action("Record Arm Track", WaveEditorAction::ToggleRecordArm),
+                    MenuItem::new_action("Tempo +5", WaveEditorAction::TempoUp),
+                    MenuItem::new_action("Tempo -5", WaveEditorAction::TempoDown),
+                ])),
+                MenuItem::new_submenu("Measure", Menu::new("Measure", vec![
+                    MenuItem::new_action("Cursors On / Off", WaveEditorAction::ToggleCursors),
+                    MenuItem::new_action("Next Cursor", WaveEditorAction::NextCursor),
+                ])),
+            ]),
+            navigator,
+            core,
+            notifier,
+        }
+    }
+
+    fn execute(&mut self, action: WaveEditorAction) {
+        match action {
+            WaveEditorAction::Core(command) => {
+                let result = self.core.borrow_mut().execute(command);
+                if let Err(e) = result {
+                    self.notifier.borrow_mut().error(&e);
+                }
+            },
+            WaveEditorAction::ZoomIn => self.wave_edit_block.zoom(1),
+            WaveEditorAction::ZoomOut => self.wave_edit_block.zoom(-1),
+            WaveEditorAction::ScaleUp => self.wave_edit_block.scale(1),
+            WaveEditorAction::ScaleDown => self.wave_edit_block.scale(-1),
+            WaveEditorAction::ToggleRecordArm => {
+                let recording = !self.core.borrow().wave_container.is_recording(self.selected_track);
+                self.execute(WaveEditorAction::Core(CoreCommand::SetRecording { track_index: self.selected_track, recording }));
+            },
+            WaveEditorAction::TempoUp | WaveEditorAction::TempoDown => {
+                let step = if matches!(action, WaveEditorAction::TempoUp) { 5.0 } else { -5.0 };
+                let tempo = self.core.borrow().tempo() + step;
+                self.execute(WaveEditorAction::Core(CoreCommand::SetTempo(tempo)));
+            },
+            WaveEditorAction::ToggleCursors => self.wave_edit_block.toggle_cursors(),
+            WaveEditorAction::NextCursor => self.wave_edit_block.next_cursor(),
+        }
+        self.wave_edit_block.block_core_update(&self.core.borrow());
+        self.wave_edit_block.block_view_update();
+    }
+
+    /// Keys of the page, dispatched by `process_key_input` and listed by the help overlay
+    fn key_bindings() -> Vec<KeyBinding<PageCommand<WaveEditorAction>>> {
+        vec![
+            KeyBinding::new("Up", "Previous track", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next track", PageCommand::Vertical(1)),
+            KeyBinding::new("Left", "Pointer or active cursor left / down", PageCommand::Horizontal(-1)),
+            KeyBinding::new("Right", "Pointer or active cursor right / up", PageCommand::Horizontal(1)),
+            KeyBinding::new("Cursor", "Next measurement cursor", PageCommand::Page(WaveEditorAction::NextCursor)),
+            KeyBinding::new("MarkIn", "Mark in", PageCommand::Page(WaveEditorAction::Core(CoreCommand::MarkIn))),
+            KeyBinding::new("MarkOut", "Mark out", PageCommand::Page(WaveEditorAction::Core(CoreCommand::MarkOut))),
+            KeyBinding::new("Copy", "Copy in/out region", PageCommand::Page(WaveEditorAction::Core(CoreCommand::Copy))),
+            KeyBinding::new("Paste", "Paste at pointer", PageCommand::Page(WaveEditorAction::Core(CoreCommand::Paste))),
+            KeyBinding::new("Insert", "Insert at pointer", PageCommand::Page(WaveEditorAction::Core(CoreCommand::Insert))),
+            KeyBinding::new("ZoomIn", "Zoom in", PageCommand::Page(WaveEditorAction::ZoomIn)),
+            KeyBinding::new("ZoomOut", "Zoom out", PageCommand::Page(WaveEditorAction::ZoomOut)),
+            KeyBinding::new("ScaleUp", "Scale up", PageCommand::Page(WaveEditorAction::ScaleUp)),
+            KeyBinding::new("ScaleDown", "Scale down", PageCommand::Page(WaveEditorAction::ScaleDown)),
+            KeyBinding::new("Play", "Play / stop", PageCommand::Page(WaveEditorAction::Core(CoreCommand::TogglePlay))),
+            KeyBinding::new("Menu", "Editor menu", PageCommand::OpenMenu),
+            KeyBinding::new("Page1", "Data loader", PageCommand::SwitchTo("data_loader")),
+            KeyBinding::new("Page3", "Operator rack", PageCommand::SwitchTo("operator_rack")),
+            KeyBinding::new("Page4", "Settings", PageCommand::Push("settings")),
+            KeyBinding::new("Overview", "Track overview", PageCommand::SwitchTo("track_overview")),
+            KeyBinding::new("Back", "Back", PageCommand::Pop),
+        ]
+    }
+
+    /// Track tabs on top, the editor below with room for its status lines
+    fn layout(track_number: usize) -> Layout {
+        Layout::column(vec![
+            Layout::row((0..track_number).map(|i| Layout::slot(&format!("tab_{}", i))).collect())
+                .size(35).padding(5).spacing(10),
+            Layout::slot("editor").padding(10),
+            Layout::space().size(45),
+        ])
+    }
+
+    fn layout_rects(track_number: usize, page_size: [usize; 2]) -> (Vec<[usize; 4]>, [usize; 4]) {
+        let rects = Self::layout(track_number).compute([0, 0, page_size[0], page_size[1]]);
+        let tab_rects = (0..track_number).map(|i| rects.get(&format!("tab_{}", i)).unwrap_or([0, 0, 0, 0])).collect();
+        (tab_rects, rects.get("editor").unwrap_or([0, 0, 0, 0]))
+    }
+
+    fn apply_layout(&mut self) {
+        let page_size = self.display_ref.borrow().viewport_size();
+        let (tab_rects, editor_rect) = Self::layout_rects(self.track_number, page_size);
+        self.tab_rects = tab_rects;
+        self.wave_edit_block.set_rect(editor_rect);
+    }
+
+    fn draw_track_tabs(&mut self) {
+        let mut display = self.display_ref.borrow_mut();
+        let core = self.core.borrow();
+        for (i, rect) in self.tab_rects.iter().enumerate() {
+            let color = if i == self.selected_track { (100,30,30) } else { (30,30,30) };
+            display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], color, true);
+            display.text(core.wave_container.get_track_name(i), 1, rect[0] + 5, rect[1] + 4, 1, 1, (0, 255, 0));
         }
     }
 }
 
 impl PageInterface for Page1WaveEditor {
     fn page_view_init(&mut self) {
-        // self.display_ref.borrow_mut().clean();
-        // // display.text("Page 1 Wave Editor", 1, 10, 10, 2, 8, (255,255,255));
-        // for i in 0..self.track_number {
-        //     self.wave_edit_blocks[i].block_view_update();
-        // }
-        self.wave_edit_blocks[0].set_selected(true);
+        self.wave_edit_block.set_selected(true);
     }
 
-    fn page_view_back(&mut self) {
+    fn page_enter(&mut self) {
+        self.selected_track = self.core.borrow().wave_container.get_selected_track();
+        self.apply_layout();
         self.display_ref.borrow_mut().clean();
-        // display.text("Page 1 Wave Editor", 1, 10, 10, 2, 8, (255,255,255));
-        for i in 0..self.track_number {
-            self.wave_edit_blocks[i].block_view_update();
-        }
+        self.draw_track_tabs();
+        self.wave_edit_block.block_core_update(&self.core.borrow());
+        self.wave_edit_block.block_view_update();
+    }
+
+    fn page_view_redraw(&mut self) {
+        self.page_enter();
     }
 
     fn process_key_input(&mut self) {
-        let key = self.key_manager.borrow_mut().check_keys();
+        let key = self.input_manager.borrow_mut().check_keys();
         if let Some(first_key) = key.get(0) {
             debug!("{:?}", first_key);
-            match first_key.as_str() {
-                "1" => self.update_page_index(0),
-                _ => {},
+            if let Some(action) = self.dispatch_key(&Self::key_bindings(), first_key) {
+                self.execute(action);
             }
         }
     }
 
     fn process_key_input_block_menu(&mut self) {
-        todo!()
+        let key = self.input_manager.borrow_mut().check_keys();
+        if let Some(first_key) = key.get(0) {
+            match self.menu.key_input(first_key) {
+                MenuResult::Selected(action) => {
+                    self.call_page();
+                    self.execute(action);
+                },
+                MenuResult::Closed => self.call_page(),
+                MenuResult::None => {},
+            }
+        }
+    }
+
+    fn process_touch_input(&mut self) {
+        let touches = self.input_manager.borrow_mut().check_touches();
+        for touch in touches {
+            if self.block_menu_called {
+                if let TouchEvent::Tap { x, y } = touch {
+                    match self.menu.touch_input(MENU_RECT, x, y) {
+                        MenuResult::Selected(action) => {
+                            self.call_page();
+                            self.execute(action);
+                        },
+                        MenuResult::Closed => self.call_page(),
+                        MenuResult::None => {},
+                    }
+                }
+                continue;
+            }
+            match touch {
+                TouchEvent::Down { x, y } | TouchEvent::Move { x, y } => {
+                    // dragging inside the editor scrubs the pointer, or the active cursor while the cursors are shown
+                    if !rect_contains(self.wave_edit_block.block_rect(), x, y) {
+                        continue;
+                    }
+                    if self.wave_edit_block.cursors_shown() {
+                        self.wave_edit_block.scrub_cursor(x, y);
+                        self.wave_edit_block.block_view_update();
+                    } else {
+                        let pointer = self.wave_edit_block.scrub_pointer(x);
+                        self.execute(WaveEditorAction::Core(CoreCommand::SetPointer(pointer)));
+                    }
+                },
+                _ => {},
+            }
+        }
     }
 
     fn navigate_vertical(&mut self, dir: isize) {
-        todo!()
+        let new_index = self.selected_track as isize + dir;
+        if new_index >= 0 && new_index < self.track_number as isize {
+            self.selected_track = new_index as usize;
+            self.execute(WaveEditorAction::Core(CoreCommand::SelectTrack(self.selected_track)));
+            self.draw_track_tabs();
+        }
     }
 
     fn navigate_horizontal(&mut self, dir: isize) {
-        todo!()
+        if self.wave_edit_block.cursors_shown() {
+            self.wave_edit_block.step_cursor(dir);
+            self.wave_edit_block.block_view_update();
+            return;
+        }
+        let pointer = self.wave_edit_block.step_pointer(dir);
+        self.execute(WaveEditorAction::Core(CoreCommand::SetPointer(pointer)));
+    }
+
+    fn open_menu(&mut self) {
+        self.block_menu_called = true;
     }
 
     fn call_block_menu(&mut self) {
-        todo!()
+        let mut display = self.display_ref.borrow_mut();
+        self.menu.draw(&mut display, MENU_RECT);
     }
 
     fn call_page(&mut self) {
-        todo!()
+        self.block_menu_called = false;
+        self.menu.reset();
+        self.page_enter();
     }
 
-    fn update_page_index(&mut self, index:usize){
-        *self.page_index_ref.borrow_mut() = index;
-        debug!("Page Index Updated: {}", index);
+    fn help(&self) -> Vec<HelpSection> {
+        let page_section = if self.block_menu_called {
+            menu_key_help()
+        } else {
+            HelpSection::from_bindings("Page", &Self::key_bindings())
+        };
+        vec![page_section, self.menu.help_section("Block: Wave Editor")]
+    }
+
+    fn navigator(&self) -> &Rc<RefCell<PageNavigator>> {
+        &self.navigator
     }
 
     fn page_view_update(&mut self) {
-        self.process_key_input();
+        if self.block_menu_called {
+            self.call_block_menu();
+            self.process_key_input_block_menu();
+            self.process_touch_input();
+        } else {
+            self.process_key_input();
+            self.process_touch_input();
+            if !self.block_menu_called && self.wave_edit_block.block_core_update(&self.core.borrow()) {
+                self.wave_edit_block.block_view_update();
+            }
+        }
     }
 }
 
@@ -574,30 +1603,189 @@ impl PageInterface for Page1WaveEditor {
 trait WaveEditorUiBlockInterface {
     fn block_view_update(&mut self);
     fn set_selected(&mut self, is_selected: bool);
+    fn block_rect(&self) -> [usize; 4];
+    fn set_rect(&mut self, rect: [usize; 4]);
+    /// Moves the pointer to the sample under the x coordinate and returns it
+    fn scrub_pointer(&mut self, x: usize) -> usize;
+    /// Moves the pointer by one screen column and returns it
+    fn step_pointer(&mut self, dir: isize) -> usize;
+    fn zoom(&mut self, dir: isize);
+    fn scale(&mut self, dir: isize);
+    fn cursors_shown(&self) -> bool;
+    fn toggle_cursors(&mut self);
+    /// Shows the measurement cursors or activates the next one, V2 is followed by T1
+    fn next_cursor(&mut self);
+    /// Moves the active cursor by one screen column or row
+    fn step_cursor(&mut self, dir: isize);
+    /// Moves the active cursor to the coordinate, x for time cursors and y for value cursors
+    fn scrub_cursor(&mut self, x: usize, y: usize);
+    /// Reads the selected track from the core, returns true when the block has to be redrawn
+    fn block_core_update(&mut self, core: &Core) -> bool;
+}
+
+/// Samples per screen column, from 8 columns per sample up to 64 samples per column
+const WAVE_EDITOR_ZOOM_LEVELS: [f32; 10] = [0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+
+const CURSOR_NAMES: [&str; 4] = ["T1", "T2", "V1", "V2"];
+
+/// Two time cursors and two value cursors, like the cursors of a bench oscilloscope
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MeasureCursors {
+    /// Sample positions of T1 and T2
+    times: [usize; 2],
+    /// Track values of V1 and V2
+    values: [i32; 2],
+    /// Index into `CURSOR_NAMES`
+    active: usize,
 }
 
 struct WaveEditorUiBlock {
     display_ref: Rc<RefCell<Display>>,
     wave_editor_block_name: String,
     is_selected: bool,
-    coordinate: [usize; 2],
-    coordinate_shift_x: usize,
-    coordinate_shift_y: usize,
-    block_ui_width: usize,
-    block_ui_height: usize,
+    rect: [usize; 4],
+    track_index: usize,
+    track: Vec<i32>,
+    pointer: usize,
+    in_out_flag: Option<(usize, usize)>,
+    zoom_level: usize,
+    view_start: usize,
+    vertical_scale: f32,
+    cursors: Option<MeasureCursors>,
+    sample_rate: f32,
 }
 
 impl WaveEditorUiBlock {
-    pub fn new (display_ref:Rc<RefCell<Display>>, coordinate:[usize;2]) -> Self {
+    pub fn new (display_ref:Rc<RefCell<Display>>, rect: [usize; 4]) -> Self {
         WaveEditorUiBlock{
             display_ref,
             wave_editor_block_name: String::from("WaveEditor"),
             is_selected: false,
-            coordinate,
-            coordinate_shift_x: 10,
-            coordinate_shift_y: 10,
-            block_ui_width: 150,
-            block_ui_height: 50,
+            rect,
+            track_index: 0,
+            track: Vec::new(),
+            pointer: 0,
+            in_out_flag: None,
+            zoom_level: 3,
+            view_start: 0,
+            vertical_scale: 1.0,
+            cursors: None,
+            sample_rate: CORE_SAMPLE_RATE,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.rect[2] - self.rect[0]
+    }
+
+    fn height(&self) -> usize {
+        self.rect[3] - self.rect[1]
+    }
+
+    fn samples_per_pixel(&self) -> f32 {
+        WAVE_EDITOR_ZOOM_LEVELS[self.zoom_level]
+    }
+
+    fn visible_samples(&self) -> usize {
+        ((self.width() as f32 * self.samples_per_pixel()) as usize).max(1)
+    }
+
+    fn sample_to_x(&self, sample: usize) -> Option<usize> {
+        if sample < self.view_start {
+            return None;
+        }
+        let x = ((sample - self.view_start) as f32 / self.samples_per_pixel()) as usize;
+        if x <= self.width() { Some(x) } else { None }
+    }
+
+    /// Scrolls so that the sample is visible
+    fn scroll_to(&mut self, sample: usize) {
+        let visible_samples = self.visible_samples();
+        if sample < self.view_start {
+            self.view_start = sample;
+        } else if sample >= self.view_start + visible_samples {
+            self.view_start = sample + 1 - visible_samples;
+        }
+    }
+
+    /// Scrolls so that the pointer stays visible
+    fn follow_pointer(&mut self) {
+        self.scroll_to(self.pointer);
+    }
+
+    /// Keeps time cursors on the samples of the track
+    fn clamp_time(&self, time: isize) -> usize {
+        time.clamp(0, self.track.len().saturating_sub(1) as isize) as usize
+    }
+
+    fn value_to_y(&self, value: i32, max_abs: i32) -> usize {
+        let half_height = self.height() as f32 / 2.0;
+        let y = half_height - value as f32 / max_abs as f32 * (half_height - 4.0) * self.vertical_scale;
+        y.clamp(0.0, self.height() as f32) as usize
+    }
+
+    fn y_to_value(&self, y: usize, max_abs: i32) -> i32 {
+        let half_height = self.height() as f32 / 2.0;
+        ((half_height - y as f32) * max_abs as f32 / ((half_height - 4.0) * self.vertical_scale)).round() as i32
+    }
+
+    fn max_abs(&self) -> i32 {
+        self.track.iter().map(|v| v.saturating_abs()).max().unwrap_or(1).max(1)
+    }
+
+    /// Cursors spread over the visible part of the track
+    fn default_cursors(&self) -> MeasureCursors {
+        let visible_samples = self.visible_samples();
+        let max_abs = self.max_abs();
+        MeasureCursors {
+            times: [
+                self.clamp_time((self.view_start + visible_samples / 4) as isize),
+                self.clamp_time((self.view_start + visible_samples * 3 / 4) as isize),
+            ],
+            values: [max_abs / 2, -max_abs / 2],
+            active: 0,
+        }
+    }
+
+    /// Readout lines of the cursors: positions, differences and the track values at T1 and T2
+    fn cursor_readout(&self, cursors: &MeasureCursors) -> Vec<String> {
+        let track_value = |time: usize| self.track.get(time).map(|v| v.to_string()).unwrap_or(String::from("-"));
+        let [t1, t2] = cursors.times;
+        let [v1, v2] = cursors.values;
+        let delta_samples = t1.abs_diff(t2);
+        let delta_seconds = delta_samples as f32 / self.sample_rate;
+        let frequency = if delta_samples > 0 { format!("{:.3} Hz", 1.0 / delta_seconds) } else { String::from("-") };
+        vec![
+            format!("T1 {} ({})  T2 {} ({})", t1, track_value(t1), t2, track_value(t2)),
+            format!("dT {} smp  {:.3} s  1/dT {}", delta_samples, delta_seconds, frequency),
+            format!("V1 {}  V2 {}  dV {}", v1, v2, v2 as i64 - v1 as i64),
+        ]
+    }
+
+    fn draw_cursors(&self, display: &mut Display, cursors: &MeasureCursors, max_abs: i32) {
+        let [x0, y0, x1, y1] = self.block_rect();
+        let cursor_color = |index: usize| if index == cursors.active { (255, 255, 255) } else { (255, 0, 255) };
+        for (index, &time) in cursors.times.iter().enumerate() {
+            if let Some(x) = self.sample_to_x(time) {
+                display.draw_line(x0 + x, y0, x0 + x, y1, cursor_color(index));
+                display.text(CURSOR_NAMES[index], 1, (x0 + x + 2).min(x1 - 14), y1 - 14, 1, 1, cursor_color(index));
+            }
+        }
+        for (index, &value) in cursors.values.iter().enumerate() {
+            let y = y0 + self.value_to_y(value, max_abs);
+            display.draw_line(x0, y, x1, y, cursor_color(index + 2));
+            display.text(CURSOR_NAMES[index + 2], 1, x0 + 2, y.saturating_sub(14).max(y0), 1, 1, cursor_color(index + 2));
+        }
+
+        // readout box in the top right corner of the editor
+        let lines = self.cursor_readout(cursors);
+        let text_width = lines.iter().map(|line| display.text_width(line, 1, 1, 1)).max().unwrap_or(0);
+        let box_x0 = x1.saturating_sub(text_width + 12).max(x0 + 1);
+        let box_y1 = y0 + 8 + lines.len() * 16;
+        display.draw_rectangle(box_x0, y0 + 1, x1 - 1, box_y1, (20, 20, 20), true);
+        display.draw_rectangle(box_x0, y0 + 1, x1 - 1, box_y1, (255, 0, 255), false);
+        for (row, line) in lines.iter().enumerate() {
+            display.text(line, 1, box_x0 + 6, y0 + 5 + row * 16, 1, 1, (255, 255, 255));
         }
     }
 }
@@ -608,23 +1796,192 @@ impl WaveEditorUiBlockInterface for WaveEditorUiBlock {
         if self.is_selected {
             color = (100,30,30);
         }
-
+        let [x0, y0, x1, y1] = self.block_rect();
         let mut display = self.display_ref.borrow_mut();
+        display.draw_rectangle(x0, y0, x1, y1, (0, 0, 0), true);
+        display.draw_rectangle(x0, y0, x1, y1, color, false);
+
+        // in/out region
+        if let Some((in_flag, out_flag)) = self.in_out_flag {
+            let (start, end) = (in_flag.min(out_flag), in_flag.max(out_flag));
+            let region_x0 = if start < self.view_start { Some(0) } else { self.sample_to_x(start) };
+            let region_x1 = self.sample_to_x(end).unwrap_or(self.width());
+            if let Some(region_x0) = region_x0 {
+                if end >= self.view_start {
+                    display.draw_rectangle(x0 + region_x0, y0 + 1, x0 + region_x1, y1 - 1, (20, 40, 20), true);
+                }
+            }
+            for (flag, label, flag_color) in [(in_flag, "I", (0, 255, 0)), (out_flag, "O", (255, 0, 0))] {
+                if let Some(x) = self.sample_to_x(flag) {
+                    display.draw_line(x0 + x, y0, x0 + x, y1, flag_color);
+                    display.text(label, 1, (x0 + x + 2).min(x1 - 8), y0 + 2, 1, 1, flag_color);
+                }
+            }
+        }
 
-        display.draw_rectangle(self.coordinate[0]+self.coordinate_shift_x,
-                               self.coordinate[1]+self.coordinate_shift_y,
-                               self.coordinate[0]+self.block_ui_width+self.coordinate_shift_x,
-                               self.coordinate[1]+self.block_ui_height+self.coordinate_shift_y,
-                               color,
-                               true);
-        display.text(&self.wave_editor_block_name, 1,
-                     self.coordinate[0]+self.coordinate_shift_x+5,
-                     self.coordinate[1]+self.coordinate_shift_y+5,
-                     1, 1, (0, 255, 0));
+        // zero line and waveform
+        let max_abs = self.max_abs();
+        let zero_y = y0 + self.value_to_y(0, max_abs);
+        display.draw_line(x0, zero_y, x1, zero_y, (60, 60, 60));
+        let samples_per_pixel = self.samples_per_pixel();
+        let mut previous: Option<(usize, usize)> = None;
+        for px in 0..=self.width() {
+            let start = self.view_start + (px as f32 * samples_per_pixel) as usize;
+            if start >= self.track.len() {
+                break;
+            }
+            let end = (self.view_start + ((px + 1) as f32 * samples_per_pixel) as usize).clamp(start + 1, self.track.len());
+            let bin = &self.track[start..end];
+            let y_max = y0 + self.value_to_y(*bin.iter().max().unwrap(), max_abs);
+            let y_min = y0 + self.value_to_y(*bin.iter().min().unwrap(), max_abs);
+            let y = y0 + self.value_to_y(bin[0], max_abs);
+            if let Some((previous_x, previous_y)) = previous {
+                display.draw_line(previous_x, previous_y, x0 + px, y, (0, 200, 255));
+            }
+            if y_max != y_min {
+                display.draw_line(x0 + px, y_max, x0 + px, y_min, (0, 200, 255));
+            }
+            previous = Some((x0 + px, y));
+        }
+
+        // pointer
+        if let Some(x) = self.sample_to_x(self.pointer) {
+            display.draw_line(x0 + x, y0, x0 + x, y1, (255, 255, 0));
+        }
+
+        if let Some(cursors) = &self.cursors {
+            self.draw_cursors(&mut display, cursors, max_abs);
+        }
 
+        // status line below the editor
+        display.draw_rectangle(x0, y1 + 5, x1, y1 + 45, (0, 0, 0), true);
+        let value = self.track.get(self.pointer).map(|v| v.to_string()).unwrap_or(String::from("-"));
+        display.text(&format!("Ptr {}  Len {}", self.pointer, self.track.len()), 1, x0, y1 + 8, 1, 1, (200, 200, 200));
+        let value_width = display.text_width(&value, 2, 1, 1);
+        display.text(&value, 2, x1 - value_width, y1 + 8, 1, 1, (255, 255, 255));
+        let in_out_text = match self.in_out_flag {
+            Some((in_flag, out_flag)) => format!("In {}  Out {}", in_flag, out_flag),
+            None => String::from("In -  Out -"),
+        };
+        display.text(&format!("{}  Zoom {}  Scale {:.1}", in_out_text, samples_per_pixel, self.vertical_scale),
+                     1, x0, y1 + 26, 1, 1, (200, 200, 200));
     }
 
     fn set_selected(&mut self, is_selected:bool) {
         self.is_selected = is_selected;
     }
+
+    fn block_rect(&self) -> [usize; 4] {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: [usize; 4]) {
+        self.rect = rect;
+    }
+
+    fn scrub_pointer(&mut self, x: usize) -> usize {
+        let rect = self.block_rect();
+        let px = x.clamp(rect[0], rect[2]) - rect[0];
+        let pointer = self.view_start + (px as f32 * self.samples_per_pixel()) as usize;
+        self.pointer = pointer.min(self.track.len().saturating_sub(1));
+        self.pointer
+    }
+
+    fn step_pointer(&mut self, dir: isize) -> usize {
+        let step = self.samples_per_pixel().max(1.0) as isize;
+        self.pointer = (self.pointer as isize + dir * step).clamp(0, self.track.len().saturating_sub(1) as isize) as usize;
+        self.follow_pointer();
+        self.pointer
+    }
+
+    fn zoom(&mut self, dir: isize) {
+        // zooming in means fewer samples per column
+        let new_level = self.zoom_level as isize - dir;
+        if new_level >= 0 && new_level < WAVE_EDITOR_ZOOM_LEVELS.len() as isize {
+            self.zoom_level = new_level as usize;
+            // keep the pointer in the middle of the view
+            self.view_start = self.pointer.saturating_sub(self.visible_samples() / 2);
+        }
+    }
+
+    fn scale(&mut self, dir: isize) {
+        self.vertical_scale = if dir > 0 { self.vertical_scale * 2.0 } else { self.vertical_scale / 2.0 };
+        self.vertical_scale = self.vertical_scale.clamp(0.125, 16.0);
+    }
+
+    fn cursors_shown(&self) -> bool {
+        self.cursors.is_some()
+    }
+
+    fn toggle_cursors(&mut self) {
+        self.cursors = match self.cursors {
+            Some(_) => None,
+            None => Some(self.default_cursors()),
+        };
+    }
+
+    fn next_cursor(&mut self) {
+        self.cursors = match self.cursors {
+            None => Some(self.default_cursors()),
+            Some(cursors) => Some(MeasureCursors { active: (cursors.active + 1) % CURSOR_NAMES.len(), ..cursors }),
+        };
+        if let Some(cursors) = &self.cursors {
+            debug!("Active cursor: {}", CURSOR_NAMES[cursors.active]);
+        }
+    }
+
+    fn step_cursor(&mut self, dir: isize) {
+        let time_step = self.samples_per_pixel().max(1.0) as isize;
+        // one screen row of the current vertical scale, at least one unit
+        let value_step = ((self.max_abs() as f32 / ((self.height() as f32 / 2.0 - 4.0) * self.vertical_scale)).max(1.0)) as i32;
+        let mut cursors = match self.cursors {
+            Some(cursors) => cursors,
+            None => return,
+        };
+        match cursors.active {
+            index @ 0..=1 => {
+                cursors.times[index] = self.clamp_time(cursors.times[index] as isize + dir * time_step);
+                self.scroll_to(cursors.times[index]);
+            },
+            index => cursors.values[index - 2] = cursors.values[index - 2].saturating_add(dir as i32 * value_step),
+        }
+        self.cursors = Some(cursors);
+    }
+
+    fn scrub_cursor(&mut self, x: usize, y: usize) {
+        let rect = self.block_rect();
+        let time = self.clamp_time((self.view_start + ((x.clamp(rect[0], rect[2]) - rect[0]) as f32 * self.samples_per_pixel()) as usize) as isize);
+        let value = self.y_to_value(y.clamp(rect[1], rect[3]) - rect[1], self.max_abs());
+        if let Some(cursors) = &mut self.cursors {
+            match cursors.active {
+                index @ 0..=1 => cursors.times[index] = time,
+                index => cursors.values[index - 2] = value,
+            }
+        }
+    }
+
+    fn block_core_update(&mut self, core: &Core) -> bool {
+        let wave_container = &core.wave_container;
+        let track_index = wave_container.get_selected_track();
+        let track = wave_container.wave_track[track_index].borrow();
+        let pointer = wave_container.get_pointer();
+        let in_out_flag = wave_container.get_in_out_flag();
+
+        let changed = track_index != self.track_index || *track != self.track
+            || pointer != self.pointer || in_out_flag != self.in_out_flag || core.sample_rate() != self.sample_rate;
+        if changed {
+            self.track_index = track_index;
+            self.track = track.clone();
+            self.pointer = pointer;
+            self.in_out_flag = in_out_flag;
+            self.sample_rate = core.sample_rate();
+            if let Some(cursors) = &mut self.cursors {
+                // the new track can be shorter
+                let last = self.track.len().saturating_sub(1);
+                cursors.times = cursors.times.map(|time| time.min(last));
+            }
+            self.follow_pointer();
+        }
+        changed
+    }
 }
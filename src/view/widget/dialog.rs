@@ -0,0 +1,107 @@
+use crate::view::display::display::Display;
+use crate::view::view_main::rect_contains;
+
+const PADDING: usize = 15;
+const BUTTON_HEIGHT: usize = 30;
+const BUTTON_WIDTH: usize = 100;
+/// Characters per message line, longer messages are wrapped at spaces
+const LINE_CHARS: usize = 50;
+const LINE_HEIGHT: usize = 18;
+
+#[derive(Debug, PartialEq)]
+pub enum DialogResult {
+    None,
+    Confirmed,
+    Cancelled,
+}
+
+/// # Dialog Widget
+/// Confirm/cancel question, `Left`/`Right` select a button, `Menu` presses it and `Back` cancels.
+pub struct Dialog {
+    title: String,
+    message: String,
+    confirm_label: String,
+    confirm_selected: bool,
+}
+
+impl Dialog {
+    pub fn new(title: &str, message: &str, confirm_label: &str) -> Self {
+        Dialog {
+            title: title.to_string(),
+            message: message.to_string(),
+            confirm_label: confirm_label.to_string(),
+            // cancel is the safe default
+            confirm_selected: false,
+        }
+    }
+
+    pub fn key_input(&mut self, key: &str) -> DialogResult {
+        match key {
+            "Left" => self.confirm_selected = false,
+            "Right" => self.confirm_selected = true,
+            "Menu" if self.confirm_selected => return DialogResult::Confirmed,
+            "Menu" | "Back" => return DialogResult::Cancelled,
+            _ => {},
+        }
+        DialogResult::None
+    }
+
+    /// Taps outside the buttons are ignored, the dialog has to be answered
+    pub fn touch_input(&mut self, rect: [usize; 4], x: usize, y: usize) -> DialogResult {
+        let [cancel_rect, confirm_rect] = Self::button_rects(rect);
+        if rect_contains(cancel_rect, x, y) {
+            return DialogResult::Cancelled;
+        }
+        if rect_contains(confirm_rect, x, y) {
+            return DialogResult::Confirmed;
+        }
+        DialogResult::None
+    }
+
+    fn button_rects(rect: [usize; 4]) -> [[usize; 4]; 2] {
+        let y0 = rect[3] - PADDING - BUTTON_HEIGHT;
+        let y1 = rect[3] - PADDING;
+        [[rect[0] + PADDING, y0, rect[0] + PADDING + BUTTON_WIDTH, y1],
+         [rect[2] - PADDING - BUTTON_WIDTH, y0, rect[2] - PADDING, y1]]
+    }
+
+    pub fn draw(&self, display: &mut Display, rect: [usize; 4]) {
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (0, 60, 100), true);
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (255, 255, 255), false);
+        display.text(&self.title, 1, rect[0] + PADDING, rect[1] + PADDING, 1, 1, (255, 255, 255));
+        for (line_index, line) in wrap_text(&self.message, LINE_CHARS).iter().enumerate() {
+            display.text(line, 1, rect[0] + PADDING, rect[1] + PADDING + (line_index + 2) * LINE_HEIGHT, 1, 1, (200, 200, 200));
+        }
+
+        let [cancel_rect, confirm_rect] = Self::button_rects(rect);
+        for (button_rect, label, selected) in [(cancel_rect, "Cancel", !self.confirm_selected), (confirm_rect, self.confirm_label.as_str(), self.confirm_selected)] {
+            let color = if selected { (100,30,30) } else { (30,30,30) };
+            display.draw_rectangle(button_rect[0], button_rect[1], button_rect[2], button_rect[3], color, true);
+            let label_width = display.text_width(label, 1, 1, 1);
+            display.text(label, 1, button_rect[0] + (BUTTON_WIDTH - label_width) / 2, button_rect[1] + 7, 1, 1, (0, 255, 0));
+        }
+    }
+}
+
+fn wrap_text(text: &str, line_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    // words longer than a line, e.g. paths, are split
+    let words = text.split_whitespace().flat_map(|word| {
+        let characters: Vec<char> = word.chars().collect();
+        characters.chunks(line_chars).map(|chunk| chunk.iter().collect::<String>()).collect::<Vec<_>>()
+    });
+    for word in words {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > line_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
@@ -0,0 +1,87 @@
+use crate::view::display::display::Display;
+use crate::view::interaction::key_binding::HelpSection;
+
+const PADDING: usize = 15;
+const LINE_HEIGHT: usize = 18;
+/// Start of the description column
+const DESCRIPTION_X: usize = 130;
+
+enum HelpLine {
+    Heading(String),
+    Binding { keys: String, description: String },
+}
+
+/// # Help Overlay
+/// Bindings of the current page and its focused block, one heading per section.
+/// `Up`/`Down` scroll, any other key or a tap closes it.
+pub struct HelpOverlay {
+    title: String,
+    lines: Vec<HelpLine>,
+    scroll_offset: usize,
+}
+
+impl HelpOverlay {
+    /// `physical_keys` resolves a logical action to the keys bound to it, unbound actions show their own name
+    pub fn new(title: &str, sections: &[HelpSection], physical_keys: impl Fn(&str) -> Vec<String>) -> Self {
+        let mut lines = Vec::new();
+        for section in sections.iter().filter(|section| !section.bindings.is_empty()) {
+            lines.push(HelpLine::Heading(section.title.clone()));
+            for (action, description) in section.bindings.iter() {
+                let keys = physical_keys(action);
+                lines.push(HelpLine::Binding {
+                    keys: if keys.is_empty() { action.clone() } else { keys.join(" / ") },
+                    description: description.clone(),
+                });
+            }
+        }
+        HelpOverlay {
+            title: format!("Help: {}", title),
+            lines,
+            scroll_offset: 0,
+        }
+    }
+
+    /// Returns true when the overlay is closed
+    pub fn key_input(&mut self, key: &str, rect: [usize; 4]) -> bool {
+        match key {
+            "Up" => self.scroll_offset = self.scroll_offset.saturating_sub(1),
+            "Down" => {
+                let max_offset = self.lines.len().saturating_sub(Self::visible_lines(rect));
+                self.scroll_offset = (self.scroll_offset + 1).min(max_offset);
+            },
+            _ => return true,
+        }
+        false
+    }
+
+    fn visible_lines(rect: [usize; 4]) -> usize {
+        ((rect[3] - rect[1]).saturating_sub(PADDING * 2 + LINE_HEIGHT * 2) / LINE_HEIGHT).max(1)
+    }
+
+    pub fn draw(&self, display: &mut Display, rect: [usize; 4]) {
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (10, 10, 40), true);
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (255, 255, 255), false);
+        display.text(&self.title, 1, rect[0] + PADDING, rect[1] + PADDING, 1, 1, (255, 255, 255));
+
+        let lines_top = rect[1] + PADDING + LINE_HEIGHT * 2;
+        let visible_lines = Self::visible_lines(rect);
+        for (row, line) in self.lines.iter().skip(self.scroll_offset).take(visible_lines).enumerate() {
+            let y = lines_top + row * LINE_HEIGHT;
+            match line {
+                HelpLine::Heading(title) => display.text(title, 1, rect[0] + PADDING, y, 1, 1, (0, 255, 0)),
+                HelpLine::Binding { keys, description } => {
+                    display.text(keys, 1, rect[0] + PADDING + 10, y, 1, 1, (255, 200, 0));
+                    display.text(description, 1, rect[0] + DESCRIPTION_X, y, 1, 1, (200, 200, 200));
+                },
+            }
+        }
+
+        // scroll markers
+        if self.scroll_offset > 0 {
+            display.text("^", 1, rect[2] - PADDING, lines_top, 1, 1, (255, 255, 255));
+        }
+        if self.scroll_offset + visible_lines < self.lines.len() {
+            display.text("v", 1, rect[2] - PADDING, lines_top + (visible_lines - 1) * LINE_HEIGHT, 1, 1, (255, 255, 255));
+        }
+    }
+}
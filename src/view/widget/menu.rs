@@ -0,0 +1,248 @@
+use crate::view::display::display::Display;
+use crate::view::interaction::key_binding::{find_command, HelpSection, KeyBinding};
+
+const TITLE_HEIGHT: usize = 50;
+const ITEM_HEIGHT: usize = 20;
+const PADDING: usize = 20;
+
+pub enum MenuEntry<A> {
+    Action(A),
+    Submenu(Menu<A>),
+}
+
+pub struct MenuItem<A> {
+    pub label: String,
+    pub enabled: bool,
+    pub entry: MenuEntry<A>,
+}
+
+impl<A> MenuItem<A> {
+    pub fn new_action(label: &str, action: A) -> Self {
+        MenuItem {
+            label: label.to_string(),
+            enabled: true,
+            entry: MenuEntry::Action(action),
+        }
+    }
+
+    pub fn new_submenu(label: &str, submenu: Menu<A>) -> Self {
+        MenuItem {
+            label: label.to_string(),
+            enabled: true,
+            entry: MenuEntry::Submenu(submenu),
+        }
+    }
+
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MenuResult<A> {
+    None,
+    Selected(A),
+    Closed,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum MenuKey {
+    Previous,
+    Next,
+    Activate,
+    Close,
+    Dismiss,
+}
+
+fn key_bindings() -> Vec<KeyBinding<MenuKey>> {
+    vec![
+        KeyBinding::new("Up", "Previous item", MenuKey::Previous),
+        KeyBinding::new("Down", "Next item", MenuKey::Next),
+        KeyBinding::new("Right", "Run item / open submenu", MenuKey::Activate),
+        KeyBinding::new("Left", "Close submenu", MenuKey::Close),
+        KeyBinding::new("Back", "Close menu", MenuKey::Close),
+        KeyBinding::new("Menu", "Close all menus", MenuKey::Dismiss),
+    ]
+}
+
+/// Keys handled by an open menu
+pub fn menu_key_help() -> HelpSection {
+    HelpSection::from_bindings("Menu", &key_bindings())
+}
+
+/// # Menu Widget
+/// List of actions and nested submenus, navigated with `Up`/`Down`,
+/// `Right` runs the item or opens its submenu, `Left`/`Back` closes the (sub)menu,
+/// `Menu` closes the whole menu and resets it.
+/// Items that do not fit into the box are scrolled.
+pub struct Menu<A> {
+    title: String,
+    items: Vec<MenuItem<A>>,
+    selected_index: usize,
+    scroll_offset: usize,
+    open_submenu: Option<usize>,
+}
+
+impl<A: Clone> Menu<A> {
+    pub fn new(title: &str, items: Vec<MenuItem<A>>) -> Self {
+        Menu {
+            title: title.to_string(),
+            items,
+            selected_index: 0,
+            scroll_offset: 0,
+            open_submenu: None,
+        }
+    }
+
+    /// Closes submenus and moves the selection back to the first item
+    pub fn reset(&mut self) {
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.open_submenu = None;
+        for item in self.items.iter_mut() {
+            if let MenuEntry::Submenu(submenu) = &mut item.entry {
+                submenu.reset();
+            }
+        }
+    }
+
+    pub fn key_input(&mut self, key: &str) -> MenuResult<A> {
+        let command = find_command(&key_bindings(), key);
+        if let Some(MenuKey::Dismiss) = command {
+            self.reset();
+            return MenuResult::Closed;
+        }
+        if let Some(index) = self.open_submenu {
+            if let MenuEntry::Submenu(submenu) = &mut self.items[index].entry {
+                return match submenu.key_input(key) {
+                    MenuResult::Closed => {
+                        self.open_submenu = None;
+                        MenuResult::None
+                    },
+                    result => result,
+                };
+            }
+        }
+
+        match command {
+            Some(MenuKey::Previous) => {
+                if self.selected_index > 0 {
+                    self.selected_index -= 1;
+                }
+            },
+            Some(MenuKey::Next) => {
+                if self.selected_index + 1 < self.items.len() {
+                    self.selected_index += 1;
+                }
+            },
+            Some(MenuKey::Activate) => return self.activate(self.selected_index),
+            Some(MenuKey::Close) => return MenuResult::Closed,
+            Some(MenuKey::Dismiss) | None => {},
+        }
+        MenuResult::None
+    }
+
+    /// Items of the menu, listed as reachable through `Menu`
+    pub fn help_section(&self, title: &str) -> HelpSection {
+        HelpSection::new(title, self.items.iter().map(|item| {
+            let label = match item.entry {
+                MenuEntry::Submenu(_) => format!("{} >", item.label),
+                MenuEntry::Action(_) => item.label.clone(),
+            };
+            let label = if item.enabled { label } else { format!("{} (disabled)", label) };
+            (String::from("Menu"), label)
+        }).collect())
+    }
+
+    /// Tapping an item selects and runs it, tapping outside the box closes the menu
+    pub fn touch_input(&mut self, rect: [usize; 4], x: usize, y: usize) -> MenuResult<A> {
+        if let Some(index) = self.open_submenu {
+            if let MenuEntry::Submenu(submenu) = &mut self.items[index].entry {
+                return match submenu.touch_input(rect, x, y) {
+                    MenuResult::Closed => {
+                        self.open_submenu = None;
+                        MenuResult::None
+                    },
+                    result => result,
+                };
+            }
+        }
+
+        if x < rect[0] || x > rect[2] || y < rect[1] || y > rect[3] {
+            return MenuResult::Closed;
+        }
+        let items_top = rect[1] + TITLE_HEIGHT;
+        if y < items_top {
+            return MenuResult::None;
+        }
+        let index = self.scroll_offset + (y - items_top) / ITEM_HEIGHT;
+        if index < self.items.len().min(self.scroll_offset + Self::visible_rows(rect)) {
+            self.selected_index = index;
+            return self.activate(index);
+        }
+        MenuResult::None
+    }
+
+    fn activate(&mut self, index: usize) -> MenuResult<A> {
+        let item = match self.items.get_mut(index) {
+            Some(item) if item.enabled => item,
+            _ => return MenuResult::None,
+        };
+        match &mut item.entry {
+            MenuEntry::Action(action) => MenuResult::Selected(action.clone()),
+            MenuEntry::Submenu(submenu) => {
+                submenu.reset();
+                self.open_submenu = Some(index);
+                MenuResult::None
+            },
+        }
+    }
+
+    fn visible_rows(rect: [usize; 4]) -> usize {
+        ((rect[3] - rect[1]).saturating_sub(TITLE_HEIGHT + PADDING) / ITEM_HEIGHT).max(1)
+    }
+
+    pub fn draw(&mut self, display: &mut Display, rect: [usize; 4]) {
+        if let Some(index) = self.open_submenu {
+            if let MenuEntry::Submenu(submenu) = &mut self.items[index].entry {
+                submenu.draw(display, rect);
+                return;
+            }
+        }
+
+        let visible_rows = Self::visible_rows(rect);
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + visible_rows {
+            self.scroll_offset = self.selected_index + 1 - visible_rows;
+        }
+
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (0, 100, 0), true);
+        display.text(&self.title, 2, rect[0] + PADDING, rect[1] + 10, 2, 8, (255, 255, 255));
+
+        let items_top = rect[1] + TITLE_HEIGHT;
+        for (row, (index, item)) in self.items.iter().enumerate().skip(self.scroll_offset).take(visible_rows).enumerate() {
+            let color = if !item.enabled {
+                (100, 100, 100)
+            } else if self.selected_index == index {
+                (255, 0, 0)
+            } else {
+                (255, 255, 255)
+            };
+            let label = match item.entry {
+                MenuEntry::Submenu(_) => format!("{} >", item.label),
+                MenuEntry::Action(_) => item.label.clone(),
+            };
+            display.text(&label, 1, rect[0] + PADDING, items_top + row * ITEM_HEIGHT, 1, 1, color);
+        }
+
+        // scroll markers
+        if self.scroll_offset > 0 {
+            display.text("^", 1, rect[2] - PADDING, items_top, 1, 1, (255, 255, 255));
+        }
+        if self.scroll_offset + visible_rows < self.items.len() {
+            display.text("v", 1, rect[2] - PADDING, items_top + (visible_rows - 1) * ITEM_HEIGHT, 1, 1, (255, 255, 255));
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod menu;
+pub mod text_input;
+pub mod dialog;
+pub mod notifier;
+pub mod status_bar;
+pub mod help_overlay;
+pub mod parameter_list;
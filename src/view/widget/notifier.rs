@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use crate::view::display::display::Display;
+use crate::view::widget::dialog::{Dialog, DialogResult};
+use crate::view::widget::text_input::{TextInput, TextInputResult};
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_millis(2500);
+/// Characters that fit into the toast box
+const TOAST_MAX_CHARS: usize = 72;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToastLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+struct Toast {
+    level: ToastLevel,
+    message: String,
+    shown_since: Option<Instant>,
+}
+
+/// # Notifier
+/// Shared with the pages to raise toasts, confirm dialogs and text inputs. The `ViewContainer` draws them
+/// above the page; while a dialog or text input is open it gets all input and the page is not updated.
+/// The answer is picked up by the page with `take_dialog_result` / `take_text_input_result` using the id
+/// it opened the dialog or text input with.
+pub struct Notifier {
+    toasts: VecDeque<Toast>,
+    dialog: Option<(String, Dialog)>,
+    dialog_results: Vec<(String, bool)>,
+    text_input: Option<(String, TextInput)>,
+    text_input_results: Vec<(String, TextInputResult)>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Notifier {
+            toasts: VecDeque::new(),
+            dialog: None,
+            dialog_results: Vec::new(),
+            text_input: None,
+            text_input_results: Vec::new(),
+        }
+    }
+
+    pub fn toast(&mut self, level: ToastLevel, message: &str) {
+        match level {
+            ToastLevel::Info => info!("Toast: {}", message),
+            _ => warn!("Toast: {}", message),
+        }
+        self.toasts.push_back(Toast { level, message: message.to_string(), shown_since: None });
+    }
+
+    pub fn info(&mut self, message: &str) {
+        self.toast(ToastLevel::Info, message);
+    }
+
+    pub fn warning(&mut self, message: &str) {
+        self.toast(ToastLevel::Warning, message);
+    }
+
+    pub fn error(&mut self, message: &str) {
+        self.toast(ToastLevel::Error, message);
+    }
+
+    /// Opens a confirm/cancel dialog, a dialog that is already open is cancelled
+    pub fn confirm(&mut self, dialog_id: &str, title: &str, message: &str, confirm_label: &str) {
+        if let Some((open_id, _)) = self.dialog.take() {
+            self.dialog_results.push((open_id, false));
+        }
+        self.dialog = Some((dialog_id.to_string(), Dialog::new(title, message, confirm_label)));
+    }
+
+    /// `Some(true)` once the dialog was confirmed, `Some(false)` once it was cancelled
+    pub fn take_dialog_result(&mut self, dialog_id: &str) -> Option<bool> {
+        let index = self.dialog_results.iter().position(|(id, _)| id == dialog_id)?;
+        Some(self.dialog_results.remove(index).1)
+    }
+
+    pub fn is_dialog_open(&self) -> bool {
+        self.dialog.is_some()
+    }
+
+    /// Returns true when the dialog was closed
+    pub fn dialog_key_input(&mut self, key: &str) -> bool {
+        let result = match &mut self.dialog {
+            Some((_, dialog)) => dialog.key_input(key),
+            None => return false,
+        };
+        self.close_dialog(result)
+    }
+
+    /// Returns true when the dialog was closed
+    pub fn dialog_touch_input(&mut self, rect: [usize; 4], x: usize, y: usize) -> bool {
+        let result = match &mut self.dialog {
+            Some((_, dialog)) => dialog.touch_input(rect, x, y),
+            None => return false,
+        };
+        self.close_dialog(result)
+    }
+
+    fn close_dialog(&mut self, result: DialogResult) -> bool {
+        let confirmed = match result {
+            DialogResult::None => return false,
+            DialogResult::Confirmed => true,
+            DialogResult::Cancelled => false,
+        };
+        if let Some((dialog_id, _)) = self.dialog.take() {
+            self.dialog_results.push((dialog_id, confirmed));
+        }
+        true
+    }
+
+    pub fn draw_dialog(&self, display: &mut Display, rect: [usize; 4]) {
+        if let Some((_, dialog)) = &self.dialog {
+            dialog.draw(display, rect);
+        }
+    }
+
+    /// Opens the keyboard to edit `text`, a text input that is already open is cancelled
+    pub fn open_text_input(&mut self, input_id: &str, title: &str, text: &str, max_length: usize) {
+        if let Some((open_id, _)) = self.text_input.take() {
+            self.text_input_results.push((open_id, TextInputResult::Cancelled));
+        }
+        self.text_input = Some((input_id.to_string(), TextInput::new(title, text, max_length)));
+    }
+
+    /// `Confirmed` with the text or `Cancelled` once the text input was closed
+    pub fn take_text_input_result(&mut self, input_id: &str) -> Option<TextInputResult> {
+        let index = self.text_input_results.iter().position(|(id, _)| id == input_id)?;
+        Some(self.text_input_results.remove(index).1)
+    }
+
+    pub fn is_text_input_open(&self) -> bool {
+        self.text_input.is_some()
+    }
+
+    /// Returns true when the text input was closed
+    pub fn text_input_key_input(&mut self, key: &str) -> bool {
+        let result = match &mut self.text_input {
+            Some((_, text_input)) => text_input.key_input(key),
+            None => return false,
+        };
+        self.close_text_input(result)
+    }
+
+    /// Returns true when the text input was closed
+    pub fn text_input_touch_input(&mut self, rect: [usize; 4], x: usize, y: usize) -> bool {
+        let result = match &mut self.text_input {
+            Some((_, text_input)) => text_input.touch_input(rect, x, y),
+            None => return false,
+        };
+        self.close_text_input(result)
+    }
+
+    fn close_text_input(&mut self, result: TextInputResult) -> bool {
+        if result == TextInputResult::None {
+            return false;
+        }
+        if let Some((input_id, _)) = self.text_input.take() {
+            self.text_input_results.push((input_id, result));
+        }
+        true
+    }
+
+    pub fn draw_text_input(&self, display: &mut Display, rect: [usize; 4]) {
+        if let Some((_, text_input)) = &self.text_input {
+            text_input.draw(display, rect);
+        }
+    }
+
+    /// Drops the current toast once its time is up, returns true when it was removed
+    pub fn update_toasts(&mut self) -> bool {
+        let expired = self.toasts.front()
+            .and_then(|toast| toast.shown_since)
+            .map_or(false, |shown_since| shown_since.elapsed() >= TOAST_DURATION);
+        if expired {
+            self.toasts.pop_front();
+        }
+        expired
+    }
+
+    /// Draws the oldest toast, toasts are shown one after another
+    pub fn draw_toast(&mut self, display: &mut Display, rect: [usize; 4]) {
+        let toast = match self.toasts.front_mut() {
+            Some(toast) => toast,
+            None => return,
+        };
+        toast.shown_since.get_or_insert_with(Instant::now);
+        let color = match toast.level {
+            ToastLevel::Info => (0, 60, 100),
+            ToastLevel::Warning => (120, 90, 0),
+            ToastLevel::Error => (140, 0, 0),
+        };
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], color, true);
+        let message: String = toast.message.chars().take(TOAST_MAX_CHARS).collect();
+        display.text(&message, 1, rect[0] + 10, rect[1] + (rect[3] - rect[1]) / 2 - 8, 1, 1, (255, 255, 255));
+    }
+}
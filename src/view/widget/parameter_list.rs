@@ -0,0 +1,142 @@
+use crate::view::display::display::Display;
+use crate::view::interaction::key_binding::{KeyBinding, PageCommand};
+use crate::view::layout::Layout;
+use crate::view::page::file_browser::shorten_end;
+use crate::view::view_main::rect_contains;
+
+const TITLE_HEIGHT: usize = 20;
+const PARAMETER_WIDTH: usize = 140;
+const ROW_HEIGHT: usize = 44;
+const SINGLE_LINE_ROW_HEIGHT: usize = 30;
+/// Width of the tap areas at both ends of a single line value that step it
+const STEP_TOUCH_WIDTH: usize = 40;
+
+/// # Parameter List Widget
+/// Column of named values, either narrow rows with the value below the name on the left of a page,
+/// e.g. the analysis pages, or full width single line rows with the value in the right half, e.g. the settings.
+/// The page keeps the values, the list keeps the selected row and draws them.
+pub struct ParameterList {
+    names: Vec<&'static str>,
+    single_line: bool,
+    selected: usize,
+    rect: [usize; 4],
+}
+
+impl ParameterList {
+    pub fn new(names: &[&'static str]) -> Self {
+        ParameterList {
+            names: names.to_vec(),
+            single_line: false,
+            selected: 0,
+            rect: [0, 0, 0, 0],
+        }
+    }
+
+    /// Name and value on one line, the value starts in the middle of the row
+    pub fn single_line(mut self) -> Self {
+        self.single_line = true;
+        self
+    }
+
+    /// Title line on top, the list in the `parameters` slot on the left and `content` on the right
+    pub fn page_layout(content: Layout) -> Layout {
+        Layout::column(vec![
+            Layout::space().size(TITLE_HEIGHT),
+            Layout::row(vec![
+                Layout::slot("parameters").size(PARAMETER_WIDTH),
+                content,
+            ]).spacing(10),
+        ]).padding(10).spacing(4)
+    }
+
+    /// Keys of a page made of a parameter list, `Left`/`Right` go to `navigate_horizontal` of the page
+    pub fn key_bindings() -> Vec<KeyBinding<PageCommand<()>>> {
+        vec![
+            KeyBinding::new("Up", "Previous parameter", PageCommand::Vertical(-1)),
+            KeyBinding::new("Down", "Next parameter", PageCommand::Vertical(1)),
+            KeyBinding::new("Left", "Previous value", PageCommand::Horizontal(-1)),
+            KeyBinding::new("Right", "Next value", PageCommand::Horizontal(1)),
+            KeyBinding::new("Back", "Back", PageCommand::Pop),
+        ]
+    }
+
+    pub fn set_rect(&mut self, rect: [usize; 4]) {
+        self.rect = rect;
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_name(&self) -> &'static str {
+        self.names[self.selected]
+    }
+
+    /// Moves the selection by `dir` rows, returns true when it changed
+    pub fn navigate(&mut self, dir: isize) -> bool {
+        let new_index = self.selected as isize + dir;
+        if new_index >= 0 && new_index < self.names.len() as isize {
+            self.selected = new_index as usize;
+            return true;
+        }
+        false
+    }
+
+    fn row_height(&self) -> usize {
+        if self.single_line { SINGLE_LINE_ROW_HEIGHT } else { ROW_HEIGHT }
+    }
+
+    /// Height of the list with all rows shown
+    pub fn height(&self) -> usize {
+        self.names.len() * self.row_height()
+    }
+
+    /// Rows that fit into the rect of the list
+    fn visible_rows(&self) -> usize {
+        let height = (self.rect[3] + 1).saturating_sub(self.rect[1]);
+        self.names.len().min((height + 5) / self.row_height())
+    }
+
+    fn row_rect(&self, index: usize) -> [usize; 4] {
+        let [x0, y0, x1, _] = self.rect;
+        let y = y0 + index * self.row_height();
+        [x0, y, x1, y + self.row_height() - 5]
+    }
+
+    /// Tapping a row selects it and returns the step for its value: the left or right half of a row,
+    /// or the ends of a single line value, give -1 and 1, the rest of a single line row 0
+    pub fn touch_input(&mut self, x: usize, y: usize) -> Option<isize> {
+        let index = (0..self.visible_rows()).find(|index| rect_contains(self.row_rect(*index), x, y))?;
+        self.selected = index;
+        let [x0, _, x1, _] = self.rect;
+        let value_x = (x0 + x1) / 2;
+        if !self.single_line {
+            Some(if x < value_x { -1 } else { 1 })
+        } else if x >= value_x && x <= value_x + STEP_TOUCH_WIDTH {
+            Some(-1)
+        } else if x + STEP_TOUCH_WIDTH >= x1 {
+            Some(1)
+        } else {
+            Some(0)
+        }
+    }
+
+    /// `value` returns the text shown for the parameter at an index
+    pub fn draw(&self, display: &mut Display, value: impl Fn(usize) -> String) {
+        for (index, name) in self.names.iter().enumerate().take(self.visible_rows()) {
+            let [x0, y, x1, y1] = self.row_rect(index);
+            let color = if index == self.selected { (100,30,30) } else { (30,30,30) };
+            display.draw_rectangle(x0, y, x1, y1, color, true);
+            if self.single_line {
+                let value_x = (x0 + x1) / 2;
+                display.text(name, 1, x0 + 10, y + 4, 1, 1, (0, 255, 0));
+                let value = shorten_end(&value(index), (x1 - value_x) / 6);
+                display.text(&value, 1, value_x, y + 4, 1, 1, (255, 255, 255));
+            } else {
+                display.text(name, 1, x0 + 5, y + 3, 1, 1, (0, 255, 0));
+                let value = shorten_end(&value(index), (x1 - x0) / 6);
+                display.text(&value, 1, x0 + 5, y + 20, 1, 1, (255, 255, 255));
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+use crate::model::core::{Core, TransportState};
+use crate::view::display::display::Display;
+
+/// Height of the bar, pages are laid out below it
+pub const STATUS_BAR_HEIGHT: usize = 20;
+/// Weight of the newest frame in the smoothed frame time
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+
+/// # Status Bar
+/// Top line with the page name, transport state, playback position, tempo,
+/// the tracks armed for recording and the smoothed frame time.
+pub struct StatusBar {
+    frame_time_ms: f32,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        StatusBar {
+            frame_time_ms: 0.0,
+        }
+    }
+
+    pub fn update_frame_time(&mut self, frame_time: Duration) {
+        let frame_time_ms = frame_time.as_secs_f32() * 1000.0;
+        self.frame_time_ms += (frame_time_ms - self.frame_time_ms) * FRAME_TIME_SMOOTHING;
+    }
+
+    /// Draws over the full width, the display viewport has to be reset
+    pub fn draw(&self, display: &mut Display, page_id: &str, core: &Core, frame_budget: Duration) {
+        let width = display.viewport_size()[0];
+        display.draw_rectangle(0, 0, width - 1, STATUS_BAR_HEIGHT - 1, (20, 20, 20), true);
+        let text_y = 2;
+        display.text(&page_title(page_id), 1, 5, text_y, 1, 1, (255, 255, 255));
+
+        let recording_tracks = core.wave_container.get_recording_tracks();
+        let (transport, transport_color) = match core.transport() {
+            TransportState::Stopped => ("STOP", (150, 150, 150)),
+            TransportState::Playing if !recording_tracks.is_empty() => ("REC", (255, 0, 0)),
+            TransportState::Playing => ("PLAY", (0, 255, 0)),
+        };
+        display.text(transport, 1, 130, text_y, 1, 1, transport_color);
+        display.text(&format!("{}/{}", core.time(), core.track_length()), 1, 175, text_y, 1, 1, (200, 200, 200));
+        display.text(&format!("{:.0} BPM", core.tempo()), 1, 260, text_y, 1, 1, (200, 200, 200));
+        if !recording_tracks.is_empty() {
+            let tracks: Vec<String> = recording_tracks.iter().map(|index| (index + 1).to_string()).collect();
+            display.text(&format!("R {}", tracks.join(",")), 1, 335, text_y, 1, 1, (255, 0, 0));
+        }
+
+        // frame time against the time one frame may take
+        let budget_ms = frame_budget.as_secs_f32() * 1000.0;
+        let frame_time_color = if self.frame_time_ms > budget_ms {
+            (255, 0, 0)
+        } else if self.frame_time_ms > budget_ms * 0.75 {
+            (255, 200, 0)
+        } else {
+            (0, 255, 0)
+        };
+        let frame_time = format!("{:.1}ms", self.frame_time_ms);
+        let frame_time_width = display.text_width(&frame_time, 1, 1, 1);
+        display.text(&frame_time, 1, width - 5 - frame_time_width, text_y, 1, 1, frame_time_color);
+    }
+}
+
+/// `wave_editor` -> `Wave Editor`
+pub fn page_title(page_id: &str) -> String {
+    page_id.split('_').map(|word| {
+        let mut characters = word.chars();
+        match characters.next() {
+            Some(first) => first.to_ascii_uppercase().to_string() + characters.as_str(),
+            None => String::new(),
+        }
+    }).collect::<Vec<_>>().join(" ")
+}
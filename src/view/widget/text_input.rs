@@ -0,0 +1,157 @@
+use crate::view::display::display::Display;
+
+const TITLE_HEIGHT: usize = 40;
+const FIELD_HEIGHT: usize = 40;
+const PADDING: usize = 10;
+
+/// Character rows of the keyboard grid, all rows have `GRID_COLUMNS` cells
+const CHARACTER_ROWS: [&str; 4] = ["1234567890", "abcdefghij", "klmnopqrst", "uvwxyz-_. "];
+const GRID_COLUMNS: usize = 10;
+/// Bottom row, each key spans two grid columns
+const ACTION_KEYS: [&str; 5] = ["Shift", "Del", "Clear", "Cancel", "OK"];
+
+#[derive(Debug, PartialEq)]
+pub enum TextInputResult {
+    None,
+    Confirmed(String),
+    Cancelled,
+}
+
+/// # Text Input Widget
+/// Grid keyboard for entering names with the arrow keys, `Menu` presses the highlighted key,
+/// `Back` deletes the last character and cancels when the text is empty.
+pub struct TextInput {
+    title: String,
+    text: String,
+    max_length: usize,
+    grid_row: usize,
+    grid_column: usize,
+    shift: bool,
+}
+
+impl TextInput {
+    pub fn new(title: &str, text: &str, max_length: usize) -> Self {
+        TextInput {
+            title: title.to_string(),
+            text: text.chars().take(max_length).collect(),
+            max_length,
+            grid_row: 0,
+            grid_column: 0,
+            shift: false,
+        }
+    }
+
+    fn grid_rows() -> usize {
+        CHARACTER_ROWS.len() + 1
+    }
+
+    pub fn key_input(&mut self, key: &str) -> TextInputResult {
+        match key {
+            "Up" => self.grid_row = (self.grid_row + Self::grid_rows() - 1) % Self::grid_rows(),
+            "Down" => self.grid_row = (self.grid_row + 1) % Self::grid_rows(),
+            "Left" => self.move_column(-1),
+            "Right" => self.move_column(1),
+            "Menu" => return self.press(self.grid_row, self.grid_column),
+            "Back" => {
+                if self.text.pop().is_none() {
+                    return TextInputResult::Cancelled;
+                }
+            },
+            _ => {},
+        }
+        TextInputResult::None
+    }
+
+    /// Action keys are two columns wide, so moving on the bottom row jumps by two
+    fn move_column(&mut self, dir: isize) {
+        let step = if self.grid_row == CHARACTER_ROWS.len() { 2 } else { 1 };
+        let column = (self.grid_column - self.grid_column % step) as isize + dir * step as isize;
+        self.grid_column = column.rem_euclid(GRID_COLUMNS as isize) as usize;
+    }
+
+    /// Tapping a key presses it, taps outside the keys are ignored while the input is open
+    pub fn touch_input(&mut self, rect: [usize; 4], x: usize, y: usize) -> TextInputResult {
+        let [grid_x0, grid_y0, grid_x1, grid_y1] = Self::grid_rect(rect);
+        if x < grid_x0 || x >= grid_x1 || y < grid_y0 || y >= grid_y1 {
+            return TextInputResult::None;
+        }
+        let row = (y - grid_y0) * Self::grid_rows() / (grid_y1 - grid_y0);
+        let column = (x - grid_x0) * GRID_COLUMNS / (grid_x1 - grid_x0);
+        self.grid_row = row;
+        self.grid_column = column;
+        self.press(row, column)
+    }
+
+    fn press(&mut self, row: usize, column: usize) -> TextInputResult {
+        if let Some(characters) = CHARACTER_ROWS.get(row) {
+            if let Some(character) = characters.chars().nth(column) {
+                if self.text.chars().count() < self.max_length {
+                    self.text.push(if self.shift { character.to_ascii_uppercase() } else { character });
+                }
+            }
+            return TextInputResult::None;
+        }
+        match ACTION_KEYS[column / 2] {
+            "Shift" => self.shift = !self.shift,
+            "Del" => {
+                self.text.pop();
+            },
+            "Clear" => self.text.clear(),
+            "Cancel" => return TextInputResult::Cancelled,
+            _ => return TextInputResult::Confirmed(self.text.clone()),
+        }
+        TextInputResult::None
+    }
+
+    fn grid_rect(rect: [usize; 4]) -> [usize; 4] {
+        [rect[0] + PADDING, rect[1] + TITLE_HEIGHT + FIELD_HEIGHT + PADDING, rect[2] - PADDING, rect[3] - PADDING]
+    }
+
+    pub fn draw(&self, display: &mut Display, rect: [usize; 4]) {
+        display.draw_rectangle(rect[0], rect[1], rect[2], rect[3], (0, 60, 100), true);
+        display.text(&self.title, 1, rect[0] + PADDING, rect[1] + 12, 1, 1, (255, 255, 255));
+        let counter = format!("{}/{}", self.text.chars().count(), self.max_length);
+        let counter_width = display.text_width(&counter, 1, 1, 1);
+        display.text(&counter, 1, rect[2] - PADDING - counter_width, rect[1] + 12, 1, 1, (200, 200, 200));
+
+        // text field with the cursor at the end
+        let field_y = rect[1] + TITLE_HEIGHT;
+        display.draw_rectangle(rect[0] + PADDING, field_y, rect[2] - PADDING, field_y + FIELD_HEIGHT - 10, (0, 0, 0), true);
+        display.text(&format!("{}_", self.text), 1, rect[0] + PADDING + 5, field_y + 7, 1, 1, (255, 255, 255));
+
+        let [grid_x0, grid_y0, grid_x1, grid_y1] = Self::grid_rect(rect);
+        let cell_width = (grid_x1 - grid_x0) / GRID_COLUMNS;
+        let cell_height = (grid_y1 - grid_y0) / Self::grid_rows();
+        for (row, characters) in CHARACTER_ROWS.iter().enumerate() {
+            for (column, character) in characters.chars().enumerate() {
+                let x = grid_x0 + column * cell_width;
+                let y = grid_y0 + row * cell_height;
+                let selected = row == self.grid_row && column == self.grid_column;
+                let color = if selected { (100,30,30) } else { (30,30,30) };
+                display.draw_rectangle(x + 1, y + 1, x + cell_width - 2, y + cell_height - 2, color, true);
+                let label = match character {
+                    ' ' => String::from("Spc"),
+                    c if self.shift => c.to_ascii_uppercase().to_string(),
+                    c => c.to_string(),
+                };
+                let label_width = display.text_width(&label, 1, 1, 1);
+                display.text(&label, 1, x + (cell_width - label_width) / 2, y + cell_height / 2 - 8, 1, 1, (255, 255, 255));
+            }
+        }
+        let y = grid_y0 + CHARACTER_ROWS.len() * cell_height;
+        for (index, label) in ACTION_KEYS.iter().enumerate() {
+            let x = grid_x0 + index * 2 * cell_width;
+            let selected = self.grid_row == CHARACTER_ROWS.len() && self.grid_column / 2 == index;
+            let color = if selected {
+                (100,30,30)
+            } else if *label == "Shift" && self.shift {
+                (30,80,30)
+            } else {
+                (30,30,30)
+            };
+            display.draw_rectangle(x + 1, y + 1, x + 2 * cell_width - 2, y + cell_height - 2, color, true);
+            let label_width = display.text_width(label, 1, 1, 1);
+            display.text(label, 1, x + (2 * cell_width - label_width) / 2, y + cell_height / 2 - 8, 1, 1, (0, 255, 0));
+        }
+    }
+}